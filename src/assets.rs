@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use crate::bindings::Bindings;
+use crate::config::Config;
+use crate::enemies;
+use crate::renderer::types::Representation;
+use crate::waves;
+
+// where the game reads everything from
+pub struct Paths<'a> {
+    pub objects_dir: &'a str,
+    pub bindings_file: &'a str,
+    pub config_file: &'a str,
+    pub enemies_file: &'a str,
+    pub stages_dir: &'a str,
+}
+
+// what the game counts on being in the files
+pub struct Requirements<'a> {
+    // object files loaded by name, with the animations played on them
+    pub objects: &'a [(&'a str, &'a [&'a str])],
+    // animations played on every enemy's views
+    pub enemy_animations: &'a [&'a str],
+}
+
+#[derive(Default)]
+pub struct Report {
+    pub objects: usize,
+    pub errors: Vec<String>,
+    // things that work but probably aren't meant to be like that
+    pub warnings: Vec<String>,
+}
+
+// loads every object file and every file referring to them,
+// the way the game would, without stopping at the first problem
+pub fn check(
+    paths: &Paths, requirements: &Requirements
+) ->
+    Result<Report, Box<dyn Error>>
+{
+    let mut report = Report::default();
+
+    let mut filenames = Vec::<String>::new();
+
+    for entry in fs::read_dir(paths.objects_dir)? {
+        let path = entry?.path();
+
+        if path.extension() == Some(OsStr::new("yaml")) {
+            if let Some(name) = path.file_name() {
+                filenames.push(name.to_string_lossy().into_owned());
+            }
+        }
+    }
+
+    filenames.sort();
+
+    // files that loaded, by name
+    let mut objects = HashMap::<String, Representation>::new();
+
+    for filename in filenames.iter() {
+        let filepath = format!("{}/{}", paths.objects_dir, filename);
+
+        report.objects += 1;
+
+        match Representation::from_file(&filepath) {
+            Ok(object) => {
+                check_frames(&filepath, &object, &mut report);
+                objects.insert(filename.clone(), object);
+            }
+            Err(err) => report.errors.push(err.to_string()),
+        }
+    }
+
+    for (filename, animations) in requirements.objects.iter() {
+        let filepath = format!("{}/{}", paths.objects_dir, filename);
+
+        match objects.get(*filename) {
+            Some(object) => check_animations(
+                &filepath, object, animations, &mut report),
+            // not loading has been reported already
+            None if filenames.iter().any(|name| name == filename) => {}
+            None => report.errors.push(format!(
+                "{}: missing, the game needs it", filepath)),
+        }
+    }
+
+    if let Err(err) = Bindings::load(paths.bindings_file) {
+        report.errors.push(err.to_string());
+    }
+
+    match Config::load(paths.config_file) {
+        Ok(config) => {
+            for layer in config.starfield.iter() {
+                if !objects.contains_key(&layer.view) {
+                    report.errors.push(format!(
+                        "{}: starfield view {} can't be loaded",
+                        paths.config_file, layer.view));
+                }
+            }
+        }
+        Err(err) => report.errors.push(err.to_string()),
+    }
+
+    let mut views = Vec::<Representation>::new();
+
+    let archetypes = match enemies::load_archetypes(
+        paths.enemies_file, paths.objects_dir, &mut views)
+    {
+        Ok(archetypes) => archetypes,
+        Err(err) => {
+            report.errors.push(err.to_string());
+
+            // stages can't be checked without knowing the enemies
+            return Ok(report);
+        }
+    };
+
+    for archetype in archetypes.iter() {
+        let context = format!(
+            "{} ({})", paths.enemies_file, archetype.name);
+
+        check_animations(
+            &context, &views[archetype.view],
+            requirements.enemy_animations, &mut report);
+
+        if archetype.damaged_view != archetype.view {
+            check_animations(
+                &context, &views[archetype.damaged_view],
+                requirements.enemy_animations, &mut report);
+        }
+    }
+
+    let kinds: Vec<&str> = archetypes.iter()
+        .map(|archetype| archetype.name.as_str())
+        .collect();
+
+    if !Path::new(paths.stages_dir).is_dir() {
+        report.errors.push(format!(
+            "{}: no such directory", paths.stages_dir));
+    } else if let Err(err) = waves::load_stages(paths.stages_dir, &kinds) {
+        report.errors.push(err.to_string());
+    }
+
+    Ok(report)
+}
+
+// animation frames are drawn where the object is,
+// so one of another size makes the object jump around
+fn check_frames(
+    filepath: &str, object: &Representation, report: &mut Report
+) {
+    let size = (object.data()[0].len(), object.data().len());
+
+    let mut names: Vec<&String> = object.animations().keys().collect();
+    names.sort();
+
+    for name in names {
+        let frames = object.animations()[name].frames();
+
+        for i in 0..frames.len() {
+            let frame_size =
+                (frames[i].data()[0].len(), frames[i].data().len());
+
+            if frame_size != size {
+                report.warnings.push(format!(
+                    "{}: frame {} of {} is {}x{}, the object {}x{}",
+                    filepath, i + 1, name,
+                    frame_size.0, frame_size.1, size.0, size.1));
+            }
+        }
+    }
+}
+
+fn check_animations(
+    context: &str,
+    object: &Representation,
+    animations: &[&str],
+    report: &mut Report
+) {
+    for name in animations.iter() {
+        if object.animation(name).is_none() {
+            report.errors.push(format!(
+                "{}: no {} animation, the game plays it", context, name));
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crossterm::input::KeyEvent;
+use yaml_rust::Yaml;
+use yaml_rust::YamlEmitter;
+use yaml_rust::YamlLoader;
+use yaml_rust::yaml::Hash;
+
+// on top of the file written by `save`
+const HEADER: &str = "\
+# Keys of every action. Arrow keys are Left/Right/Up/Down,
+# other special keys are Enter, Esc, Space, Tab, Backspace
+# and F1..F12, anything else is a single character.
+";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Start,
+    Quit,
+    Pause,
+    Settings,
+}
+
+impl Action {
+    pub fn all() -> [Action; 7] {
+        [
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::Fire,
+            Action::Start,
+            Action::Quit,
+            Action::Pause,
+            Action::Settings,
+        ]
+    }
+
+    // key in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Fire => "fire",
+            Action::Start => "start",
+            Action::Quit => "quit",
+            Action::Pause => "pause",
+            Action::Settings => "settings",
+        }
+    }
+
+    fn default_keys(self) -> Vec<KeyEvent> {
+        match self {
+            Action::MoveLeft =>
+                vec![KeyEvent::Left, KeyEvent::Char('a')],
+            Action::MoveRight =>
+                vec![KeyEvent::Right, KeyEvent::Char('d')],
+            Action::Fire =>
+                vec![KeyEvent::Up, KeyEvent::Char('w'), KeyEvent::Char(' ')],
+            Action::Start =>
+                vec![KeyEvent::Enter],
+            Action::Quit =>
+                vec![KeyEvent::Esc],
+            Action::Pause =>
+                vec![KeyEvent::Char('p')],
+            Action::Settings =>
+                vec![KeyEvent::Char('o')],
+        }
+    }
+}
+
+pub struct Bindings {
+    // keys of every action in the order they were given
+    keys: HashMap<Action, Vec<KeyEvent>>,
+    actions: HashMap<KeyEvent, Action>,
+}
+
+impl Bindings {
+    pub fn new() -> Bindings {
+        let mut bindings = Bindings {
+            keys: HashMap::new(),
+            actions: HashMap::new(),
+        };
+
+        for action in Action::all().iter() {
+            bindings.bind(*action, action.default_keys());
+        }
+
+        bindings
+    }
+
+    // actions the file doesn't mention keep their default keys,
+    // a missing file means all defaults
+    pub fn load(
+        filepath: &str
+    ) ->
+        Result<Bindings, Box<dyn Error>>
+    {
+        let mut bindings = Bindings::new();
+
+        if !Path::new(filepath).exists() {
+            return Ok(bindings);
+        }
+
+        let contents = fs::read_to_string(filepath)?;
+        let docs = YamlLoader::load_from_str(&contents)?;
+
+        let doc = match docs.first() {
+            Some(doc) => doc,
+            None => return Ok(bindings),
+        };
+
+        // actions the file gives every key to, so a key can't end up
+        // doing one thing or the other depending on the order
+        let mut given = HashMap::<KeyEvent, Action>::new();
+
+        for action in Action::all().iter() {
+            let names = match doc[action.name()].as_vec() {
+                Some(names) => names,
+                None => continue,
+            };
+
+            let mut keys = Vec::<KeyEvent>::new();
+
+            for name in names {
+                let key = name.as_str()
+                    .and_then(parse_key)
+                    .ok_or_else(|| format!(
+                        "{}: unknown key {:?} for {}",
+                        filepath, name, action.name()))?;
+
+                match given.insert(folded(&key), *action) {
+                    Some(other) if other != *action =>
+                        return Err(format!(
+                            "{}: {} is bound to both {} and {}",
+                            filepath, key_label(&key),
+                            other.name(), action.name()).into()),
+                    _ => {}
+                }
+
+                keys.push(key);
+            }
+
+            bindings.bind(*action, keys);
+        }
+
+        // like `rebind`, nothing can be left without a key, whether the
+        // file gives it none or gives its keys to something else
+        for action in Action::all().iter() {
+            if bindings.keys(*action).is_empty() {
+                return Err(format!(
+                    "{}: {} is left without any key",
+                    filepath, action.name()).into());
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    pub fn save(
+        &self, filepath: &str
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        let mut hash = Hash::new();
+
+        for action in Action::all().iter() {
+            let names = (*self).keys(*action).iter()
+                .filter_map(key_name)
+                .map(Yaml::String)
+                .collect();
+
+            hash.insert(
+                Yaml::String(action.name().to_string()),
+                Yaml::Array(names));
+        }
+
+        let mut contents = HEADER.to_string();
+        YamlEmitter::new(&mut contents)
+            .dump(&Yaml::Hash(hash))?;
+        contents.push('\n');
+
+        fs::write(filepath, contents)?;
+
+        Ok(())
+    }
+
+    // makes `key` the only key of `action`, unless that
+    // would leave another action without any keys
+    pub fn rebind(
+        &mut self, action: Action, key: KeyEvent
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        if key_name(&key).is_none() {
+            return Err(format!(
+                "{} can't be bound", key_label(&key)).into());
+        }
+
+        for other in Action::all().iter() {
+            let keys = (*self).keys(*other);
+
+            if
+                *other != action &&
+                !keys.is_empty() &&
+                keys.iter().all(|bound| folded(bound) == folded(&key))
+            {
+                return Err(format!(
+                    "{} is the only key for {}",
+                    key_label(&key), other.name()).into());
+            }
+        }
+
+        (*self).bind(action, vec![key]);
+
+        Ok(())
+    }
+
+    // a key another action has is taken away from it
+    fn bind(&mut self, action: Action, keys: Vec<KeyEvent>) {
+        (*self).keys.remove(&action);
+        (*self).actions.retain(|_, bound| *bound != action);
+
+        for key in keys.iter() {
+            let key = folded(key);
+
+            (*self).actions.retain(|bound, _| folded(bound) != key);
+
+            for other in (*self).keys.values_mut() {
+                other.retain(|bound| folded(bound) != key);
+            }
+        }
+
+        for key in keys.iter() {
+            (*self).actions.insert(*key, action);
+
+            // letters work regardless of shift and caps lock
+            if let KeyEvent::Char(c) = key {
+                if c.is_ascii_alphabetic() {
+                    (*self).actions.insert(
+                        KeyEvent::Char(c.to_ascii_uppercase()), action);
+                    (*self).actions.insert(
+                        KeyEvent::Char(c.to_ascii_lowercase()), action);
+                }
+            }
+        }
+
+        (*self).keys.insert(action, keys);
+    }
+
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        (*self).actions.get(key).cloned()
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyEvent] {
+        match (*self).keys.get(&action) {
+            Some(keys) => keys,
+            None => &[],
+        }
+    }
+}
+
+// letters are the same key whatever their case
+fn folded(key: &KeyEvent) -> KeyEvent {
+    match key {
+        KeyEvent::Char(c) if c.is_ascii_alphabetic() =>
+            KeyEvent::Char(c.to_ascii_lowercase()),
+        other => *other,
+    }
+}
+
+pub fn parse_key(name: &str) -> Option<KeyEvent> {
+    let key = match name {
+        "Left" => KeyEvent::Left,
+        "Right" => KeyEvent::Right,
+        "Up" => KeyEvent::Up,
+        "Down" => KeyEvent::Down,
+        "Enter" => KeyEvent::Enter,
+        "Esc" => KeyEvent::Esc,
+        "Space" => KeyEvent::Char(' '),
+        "Tab" => KeyEvent::Tab,
+        "Backspace" => KeyEvent::Backspace,
+        "Delete" => KeyEvent::Delete,
+        "Insert" => KeyEvent::Insert,
+        "Home" => KeyEvent::Home,
+        "End" => KeyEvent::End,
+        "PageUp" => KeyEvent::PageUp,
+        "PageDown" => KeyEvent::PageDown,
+        _ => {
+            let mut chars = name.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some('F'), Some(_)) =>
+                    KeyEvent::F(name[1..].parse().ok()?),
+                (Some(c), None) =>
+                    KeyEvent::Char(c),
+                _ => return None,
+            }
+        }
+    };
+
+    Some(key)
+}
+
+// name of a key in the config file, `None` for
+// the keys that can't be bound, like Ctrl-C
+pub fn key_name(key: &KeyEvent) -> Option<String> {
+    let name = match key {
+        KeyEvent::Left => "Left".to_string(),
+        KeyEvent::Right => "Right".to_string(),
+        KeyEvent::Up => "Up".to_string(),
+        KeyEvent::Down => "Down".to_string(),
+        KeyEvent::Enter => "Enter".to_string(),
+        KeyEvent::Esc => "Esc".to_string(),
+        KeyEvent::Char(' ') => "Space".to_string(),
+        KeyEvent::Tab => "Tab".to_string(),
+        KeyEvent::Backspace => "Backspace".to_string(),
+        KeyEvent::Delete => "Delete".to_string(),
+        KeyEvent::Insert => "Insert".to_string(),
+        KeyEvent::Home => "Home".to_string(),
+        KeyEvent::End => "End".to_string(),
+        KeyEvent::PageUp => "PageUp".to_string(),
+        KeyEvent::PageDown => "PageDown".to_string(),
+        KeyEvent::F(n) => format!("F{}", n),
+        KeyEvent::Char(c) if !c.is_control() => c.to_string(),
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+// how a key is shown to the player
+pub fn key_label(key: &KeyEvent) -> String {
+    match key {
+        KeyEvent::Left => "←".to_string(),
+        KeyEvent::Right => "→".to_string(),
+        KeyEvent::Up => "↑".to_string(),
+        KeyEvent::Down => "↓".to_string(),
+        KeyEvent::Enter => "Enter".to_string(),
+        KeyEvent::Esc => "Esc".to_string(),
+        KeyEvent::Char(' ') => "Space".to_string(),
+        KeyEvent::Char(c) => c.to_uppercase().to_string(),
+        KeyEvent::F(n) => format!("F{}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use crossterm::input::KeyEvent;
+
+    use crate::bindings::Action;
+    use crate::bindings::Bindings;
+
+    // loads a bindings file with the given contents
+    fn load(name: &str, contents: &str) -> Result<Bindings, String> {
+        let filepath = env::temp_dir()
+            .join(format!("bindings-test-{}-{}.yaml", name, process::id()))
+            .to_string_lossy()
+            .into_owned();
+
+        fs::write(&filepath, contents).unwrap();
+        let bindings = Bindings::load(&filepath);
+        fs::remove_file(&filepath).unwrap();
+
+        bindings.map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn rebinding_takes_the_key_from_its_old_action() {
+        let mut bindings = Bindings::new();
+
+        bindings.rebind(Action::Fire, KeyEvent::Char('A')).unwrap();
+
+        assert_eq!(bindings.keys(Action::Fire), &[KeyEvent::Char('A')]);
+        assert_eq!(bindings.keys(Action::MoveLeft), &[KeyEvent::Left]);
+        assert_eq!(
+            bindings.action(&KeyEvent::Char('a')), Some(Action::Fire));
+        // keys the action had before do nothing now
+        assert_eq!(bindings.action(&KeyEvent::Char(' ')), None);
+    }
+
+    #[test]
+    fn rebinding_keeps_every_action_a_key() {
+        let mut bindings = Bindings::new();
+
+        assert!(bindings.rebind(Action::Start, KeyEvent::Char('P')).is_err());
+        assert!(bindings.rebind(Action::Start, KeyEvent::Ctrl('c')).is_err());
+
+        assert_eq!(
+            bindings.action(&KeyEvent::Char('p')), Some(Action::Pause));
+        assert_eq!(bindings.keys(Action::Start), &[KeyEvent::Enter]);
+    }
+
+    #[test]
+    fn files_leaving_actions_without_keys_are_refused() {
+        assert!(load("defaults", "fire: [f]\n").is_ok());
+
+        assert!(load("twice", "quit: [p]\npause: [P]\n")
+            .err().unwrap_or_default().contains("bound to both quit and pause"));
+        assert!(load("empty", "start: []\n")
+            .err().unwrap_or_default().contains("start is left without any key"));
+        assert!(load("taken", "quit: [p]\n")
+            .err().unwrap_or_default().contains("pause is left without any key"));
+    }
+}
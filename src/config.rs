@@ -0,0 +1,162 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use yaml_rust::Yaml;
+use yaml_rust::YamlLoader;
+
+use crate::yaml;
+
+// smallest playfield the stages' formations and paths fit in,
+// formations are laid out for its width
+pub const MIN_PLAYFIELD: (u16, u16) = (50, 24);
+
+pub struct Config {
+    // columns and rows the game is played in,
+    // the side panel comes on top of that
+    pub playfield: (u16, u16),
+    // turrets the player starts with, the one in play included
+    pub lives: u8,
+    // seconds between losing a turret and the next one showing up
+    pub respawn_time: f32,
+    // seconds a new turret blinks and can't be hit
+    pub invulnerable_time: f32,
+    // background layers, farthest first
+    pub starfield: Vec<StarLayer>,
+}
+
+pub struct StarLayer {
+    // file in res/objects
+    pub view: String,
+    // how many of them are on the screen at once
+    pub density: usize,
+    // cells per second
+    pub speed: f32,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config {
+            playfield: (50, 30),
+            lives: 3,
+            respawn_time: 1.5,
+            invulnerable_time: 2.0,
+            starfield: vec![
+                StarLayer {
+                    view: "snowflake2.yaml".to_string(),
+                    density: 24,
+                    speed: 2.0,
+                },
+                StarLayer {
+                    view: "snowflake.yaml".to_string(),
+                    density: 3,
+                    speed: 5.0,
+                },
+            ],
+        }
+    }
+
+    // settings the file doesn't mention keep their defaults,
+    // a missing file means all defaults
+    pub fn load(
+        filepath: &str
+    ) ->
+        Result<Config, Box<dyn Error>>
+    {
+        let mut config = Config::new();
+
+        if !Path::new(filepath).exists() {
+            return Ok(config);
+        }
+
+        let contents = fs::read_to_string(filepath)?;
+        let docs = YamlLoader::load_from_str(&contents)?;
+
+        let doc = match docs.first() {
+            Some(doc) => doc,
+            None => return Ok(config),
+        };
+
+        if !doc["playfield"].is_badvalue() {
+            let field = |name: &str| {
+                format!("{}: playfield needs a {} in cells",
+                    filepath, name)
+            };
+
+            let width = doc["playfield"]["width"].as_i64()
+                .filter(|width| *width > 0 && *width <= 1000)
+                .ok_or_else(|| field("width"))?;
+            let height = doc["playfield"]["height"].as_i64()
+                .filter(|height| *height > 0 && *height <= 1000)
+                .ok_or_else(|| field("height"))?;
+
+            config.playfield = (width as u16, height as u16);
+            check_playfield(config.playfield)
+                .map_err(|err| format!("{}: {}", filepath, err))?;
+        }
+
+        if !doc["lives"].is_badvalue() {
+            config.lives = match doc["lives"].as_i64() {
+                Some(lives) if lives > 0 && lives < 100 => lives as u8,
+                _ => return Err(format!(
+                    "{}: lives must be a number from 1 to 99",
+                    filepath).into()),
+            };
+        }
+
+        if !doc["respawn_time"].is_badvalue() {
+            config.respawn_time =
+                non_negative(&doc["respawn_time"])
+                    .ok_or_else(|| format!(
+                        "{}: respawn_time must be seconds", filepath))?;
+        }
+
+        if !doc["invulnerable_time"].is_badvalue() {
+            config.invulnerable_time =
+                non_negative(&doc["invulnerable_time"])
+                    .ok_or_else(|| format!(
+                        "{}: invulnerable_time must be seconds", filepath))?;
+        }
+
+        if let Some(layers) = doc["starfield"].as_vec() {
+            config.starfield = Vec::new();
+
+            for layer in layers {
+                let field = |name: &str| {
+                    format!("{}: every starfield layer needs {}",
+                        filepath, name)
+                };
+
+                config.starfield.push(StarLayer {
+                    view: layer["view"].as_str()
+                        .ok_or_else(|| field("a view"))?.to_string(),
+                    density: layer["density"].as_i64()
+                        .filter(|density| *density >= 0)
+                        .ok_or_else(|| field("a density"))? as usize,
+                    speed: non_negative(&layer["speed"])
+                        .ok_or_else(|| field("a speed"))?,
+                });
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+pub fn check_playfield(
+    size: (u16, u16)
+) ->
+    Result<(), Box<dyn Error>>
+{
+    if size.0 < MIN_PLAYFIELD.0 || size.1 < MIN_PLAYFIELD.1 {
+        return Err(format!(
+            "playfield must be at least {}x{}, not {}x{}",
+            MIN_PLAYFIELD.0, MIN_PLAYFIELD.1, size.0, size.1).into());
+    }
+
+    Ok(())
+}
+
+fn non_negative(value: &Yaml) -> Option<f32> {
+    yaml::as_f32(value).filter(|number| *number >= 0.0)
+}
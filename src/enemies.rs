@@ -0,0 +1,116 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs;
+
+use yaml_rust::Yaml;
+use yaml_rust::YamlLoader;
+
+use crate::renderer::types::Representation;
+use crate::yaml;
+
+// how an enemy attacks once it's in the formation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Behavior {
+    // straight down at the turret
+    Dive,
+    // zigzags its way down
+    Swoop,
+    // dives, or goes for the turret with its tractor beam
+    Capture,
+}
+
+pub struct Archetype {
+    // what stage groups refer to it by
+    pub name: String,
+    pub view: usize,
+    // view once it's been hit but still flies
+    pub damaged_view: usize,
+    pub hit_points: u8,
+    pub formation_score: u32,
+    pub diving_score: u32,
+    // multiplier of the stage's speed
+    pub speed: f32,
+    pub behavior: Behavior,
+}
+
+// views are loaded from `objects_dir` and added to `views`
+pub fn load_archetypes(
+    filepath: &str,
+    objects_dir: &str,
+    views: &mut Vec<Representation>
+) ->
+    Result<Vec<Archetype>, Box<dyn Error>>
+{
+    let contents = fs::read_to_string(filepath)?;
+    let docs = YamlLoader::load_from_str(&contents)?;
+
+    let entries = docs.first()
+        .and_then(Yaml::as_hash)
+        .ok_or_else(|| format!("{}: no enemies", filepath))?;
+
+    let mut archetypes = Vec::<Archetype>::new();
+
+    for (name, entry) in entries {
+        let name = name.as_str()
+            .ok_or_else(|| format!(
+                "{}: enemy names must be strings", filepath))?;
+
+        let field = |field: &str| {
+            format!("{}: {} needs {}", filepath, name, field)
+        };
+
+        let mut load = |key: &str| -> Result<usize, Box<dyn Error>> {
+            let file = entry[key].as_str()
+                .ok_or_else(|| field(key))?;
+
+            views.push(Representation::from_file(
+                &format!("{}/{}", objects_dir, file))?);
+
+            Ok(views.len() - 1)
+        };
+
+        let view = load("view")?;
+        let damaged_view =
+            if entry["damaged_view"].is_badvalue() {
+                view
+            } else {
+                load("damaged_view")?
+            };
+
+        let hit_points = match entry["hit_points"].as_i64() {
+            Some(points) if points > 0 && points < 256 => points as u8,
+            _ => return Err(field("hit_points from 1 to 255").into()),
+        };
+
+        let behavior = match entry["behavior"].as_str() {
+            Some("dive") => Behavior::Dive,
+            Some("swoop") => Behavior::Swoop,
+            Some("capture") => Behavior::Capture,
+            _ => return Err(
+                field("behavior: dive, swoop or capture").into()),
+        };
+
+        let speed = match &entry["speed"] {
+            Yaml::BadValue => 1.0,
+            speed => yaml::as_f32(speed)
+                .ok_or_else(|| field("speed as a number"))?,
+        };
+
+        archetypes.push(Archetype {
+            name: name.to_string(),
+            view: view,
+            damaged_view: damaged_view,
+            hit_points: hit_points,
+            formation_score: entry["score"]["formation"].as_i64()
+                .and_then(|score| u32::try_from(score).ok())
+                .ok_or_else(|| field("score: formation"))?,
+            diving_score: entry["score"]["diving"].as_i64()
+                .and_then(|score| u32::try_from(score).ok())
+                .ok_or_else(|| field("score: diving"))?,
+            speed: speed,
+            behavior: behavior,
+        });
+    }
+
+    Ok(archetypes)
+}
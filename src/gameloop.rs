@@ -5,50 +5,349 @@ use std::thread;
 
 use crossterm::input::InputEvent;
 
+use crate::recording::Recorder;
+use crate::renderer::types::Backend;
 use crate::renderer::types::Renderer;
 
-pub fn gameloop<T>(
+const MAX_CATCH_UP_STEPS: u32 = 8;
+
+type RenderFn<T, B> =
+    fn(&mut T, &mut Renderer<B>, f32) -> Result<(), Box<dyn Error>>;
+
+// how much game time a single `update` call covers
+pub enum Timestep {
+    // time actually passed since the previous frame
+    Variable,
+    // always the same step, `update` is called as many
+    // times as needed to catch up with the clock
+    Fixed(Duration),
+}
+
+pub trait Clock {
+    // time passed since the clock was created
+    fn now(&mut self) -> Duration;
+
+    fn sleep(&mut self, dur: Duration);
+}
+
+pub struct SystemClock {
+    timer: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            timer: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> Duration {
+        (*self).timer.elapsed()
+    }
+
+    fn sleep(&mut self, dur: Duration) {
+        thread::sleep(dur);
+    }
+}
+
+// clock that only moves when slept on,
+// so every frame takes exactly the preferred duration
+pub struct ManualClock {
+    now: Duration,
+    // actually sleep as well, so the game still
+    // runs at a watchable speed
+    paced: bool,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock {
+            now: Duration::from_secs(0),
+            paced: false,
+        }
+    }
+
+    pub fn paced() -> ManualClock {
+        ManualClock {
+            now: Duration::from_secs(0),
+            paced: true,
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&mut self) -> Duration {
+        (*self).now
+    }
+
+    fn sleep(&mut self, dur: Duration) {
+        if (*self).paced {
+            thread::sleep(dur);
+        }
+
+        (*self).now += dur;
+    }
+}
+
+// `render` gets the interpolation alpha: how far (0..1) the
+// clock has got from the last update towards the next one,
+// `recorder` gets every input event along with its frame
+#[allow(clippy::too_many_arguments)]
+pub fn gameloop<T, B: Backend, C: Clock + ?Sized>(
     fps: u8,
+    timestep: Timestep,
+    clock: &mut C,
     mut state: T,
-    mut renderer: Renderer,
+    renderer: &mut Renderer<B>,
+    mut recorder: Option<&mut Recorder>,
     input: fn(&mut T, Vec<InputEvent>, &mut bool) -> (),
     update: fn(&mut T, Duration) -> (),
-    render: fn(&mut T, &mut Renderer) -> Result<(), Box<dyn Error>>,
+    render: RenderFn<T, B>,
 ) ->
     Result<(), Box<dyn Error>>
 {
-    let timer = Instant::now();
-
     let pref_loop_dur = Duration::from_millis(1000 / fps as u64);
     let mut curr_loop_start;
-    let mut prev_loop_start = timer.elapsed();
+    let mut prev_loop_start = clock.now();
+
+    // game time not yet consumed by fixed steps
+    let mut accumulator = Duration::from_secs(0);
+
+    let mut frame: u64 = 0;
 
     let mut proceed = true;
     while proceed {
-        curr_loop_start = timer.elapsed();
+        curr_loop_start = clock.now();
         let prev_loop_dur = curr_loop_start - prev_loop_start;
         prev_loop_start = curr_loop_start;
 
+        let events = renderer.events();
+
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(frame, &events)?;
+        }
+
         input(
             &mut state,
-            renderer.events(),
+            events,
             &mut proceed);
 
-        update(
-            &mut state,
-            prev_loop_dur);
+        let alpha = match timestep {
+            Timestep::Variable => {
+                update(
+                    &mut state,
+                    prev_loop_dur);
+
+                1.0
+            }
+            Timestep::Fixed(step) => {
+                // don't try to catch up after long stalls,
+                // the game would only freeze even longer
+                accumulator = (accumulator + prev_loop_dur)
+                    .min(step * MAX_CATCH_UP_STEPS);
+
+                while accumulator >= step {
+                    update(
+                        &mut state,
+                        step);
+
+                    accumulator -= step;
+                }
+
+                accumulator.as_secs_f32() / step.as_secs_f32()
+            }
+        };
 
         render(
             &mut state,
-            &mut renderer)?;
+            renderer,
+            alpha)?;
 
-        let curr_loop_end = timer.elapsed();
+        let curr_loop_end = clock.now();
         let curr_loop_dur = curr_loop_end - curr_loop_start;
 
         if pref_loop_dur > curr_loop_dur {
-            thread::sleep(pref_loop_dur - curr_loop_dur);
+            clock.sleep(pref_loop_dur - curr_loop_dur);
         }
+
+        frame += 1;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use std::fs;
+    use std::process;
+    use std::time::Duration;
+
+    use crossterm::input::InputEvent;
+    use crossterm::input::KeyEvent;
+    use rand::Rng;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::gameloop;
+    use crate::gameloop::ManualClock;
+    use crate::gameloop::Timestep;
+    use crate::recording::Recorder;
+    use crate::recording::Replay;
+    use crate::renderer::types::Layer;
+    use crate::renderer::types::Location;
+    use crate::renderer::types::MemoryBackend;
+    use crate::renderer::types::Renderer;
+    use crate::renderer::types::Representation;
+
+    const STEP: Duration = Duration::from_millis(1000 / 30);
+
+    // drops falling at random places and a player moved by the keys
+    struct Drops {
+        rng: StdRng,
+        player: i32,
+        drops: Vec<(f32, f32)>,
+    }
+
+    fn input(state: &mut Drops, inputs: Vec<InputEvent>, proceed: &mut bool) {
+        for event in inputs {
+            match event {
+                InputEvent::Keyboard(KeyEvent::Left) => (*state).player -= 1,
+                InputEvent::Keyboard(KeyEvent::Right) => (*state).player += 1,
+                InputEvent::Keyboard(KeyEvent::Ctrl('c')) => *proceed = false,
+                _ => {}
+            }
+        }
+    }
+
+    fn update(state: &mut Drops, delta: Duration) {
+        assert_eq!(delta, STEP);
+
+        let dt = delta.as_secs_f32();
+
+        for drop in (*state).drops.iter_mut() {
+            drop.1 += 7.0 * dt;
+        }
+
+        if (*state).rng.gen_bool(0.2) {
+            let x = (*state).rng.gen_range(0, 12);
+            (*state).drops.push((x as f32, 0.0));
+        }
+    }
+
+    fn render(
+        state: &mut Drops,
+        renderer: &mut Renderer<MemoryBackend>,
+        _alpha: f32
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        let drop_view = Representation::new('\0', vec![vec!['*']]);
+        let player_view = Representation::new('\0', vec![vec!['^']]);
+
+        let mut locations = Vec::<Location>::new();
+
+        for drop in (*state).drops.iter() {
+            locations.push(Location { x: drop.0 as i32, y: drop.1 as i32 });
+        }
+
+        let player_location = Location { x: (*state).player, y: 5 };
+
+        let mut render_queue =
+            Vec::<(Layer, &Location, &Representation)>::new();
+
+        for location in locations.iter() {
+            render_queue.push((Layer::Gameplay, location, &drop_view));
+        }
+        render_queue.push((Layer::Hud, &player_location, &player_view));
+
+        renderer.render(&render_queue)
+    }
+
+    // renderer with keys scripted up to
+    // a Ctrl-C on the given frame
+    fn scripted(frames: u64) -> Renderer<MemoryBackend> {
+        let mut renderer = Renderer::new(MemoryBackend::new(12, 6));
+
+        let script = [
+            (3, KeyEvent::Left),
+            (10, KeyEvent::Left),
+            (11, KeyEvent::Right),
+            (25, KeyEvent::Left),
+            (frames, KeyEvent::Ctrl('c')),
+        ];
+        for (frame, key) in script.iter() {
+            renderer.backend_mut().script(*frame, InputEvent::Keyboard(*key));
+        }
+
+        renderer
+    }
+
+    fn play(
+        seed: u64,
+        renderer: &mut Renderer<MemoryBackend>,
+        recorder: Option<&mut Recorder>
+    ) {
+        let state = Drops {
+            rng: StdRng::seed_from_u64(seed),
+            player: 6,
+            drops: Vec::new(),
+        };
+
+        let mut clock = ManualClock::new();
+
+        gameloop::gameloop(
+            30, Timestep::Fixed(STEP), &mut clock, state, renderer,
+            recorder, input, update, render).unwrap();
+    }
+
+    // last frame of a run of the given number of frames
+    fn run(seed: u64, frames: u64) -> String {
+        let mut renderer = scripted(frames);
+
+        play(seed, &mut renderer, None);
+
+        renderer.backend().snapshot()
+    }
+
+    #[test]
+    fn fixed_step_runs_are_reproducible() {
+        let first = run(7, 60);
+
+        assert_eq!(first, run(7, 60));
+        // the seed is what decides, not anything left over
+        assert_ne!(first, run(8, 60));
+    }
+
+    #[test]
+    fn replays_play_out_like_the_recorded_run() {
+        let filepath = env::temp_dir()
+            .join(format!("replay-test-{}.txt", process::id()))
+            .to_string_lossy()
+            .into_owned();
+
+        let mut recorded = scripted(60);
+        let mut recorder = Recorder::create(&filepath, 7).unwrap();
+        play(7, &mut recorded, Some(&mut recorder));
+        drop(recorder);
+
+        let replay = Replay::load(&filepath).unwrap();
+        fs::remove_file(&filepath).unwrap();
+
+        assert_eq!(replay.seed(), Some(7));
+
+        // nothing scripted, every key comes from the recording
+        let mut replayed = Renderer::new(MemoryBackend::new(12, 6));
+        replayed.replay(replay);
+        // so a replay that loses the recorded Ctrl-C still ends
+        replayed.backend_mut().script(
+            120, InputEvent::Keyboard(KeyEvent::Ctrl('c')));
+        play(7, &mut replayed, None);
+
+        assert_eq!(
+            recorded.backend().snapshot(), replayed.backend().snapshot());
+    }
+}
@@ -0,0 +1,165 @@
+use std::cmp::Reverse;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use yaml_rust::Yaml;
+use yaml_rust::YamlEmitter;
+use yaml_rust::YamlLoader;
+use yaml_rust::yaml::Hash;
+
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    // YYYY-MM-DD
+    pub date: String,
+    pub seed: u64,
+}
+
+pub struct HighScores {
+    capacity: usize,
+    // best first
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn new(capacity: usize) -> HighScores {
+        HighScores {
+            capacity: capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    // a missing file is just an empty table
+    pub fn load(
+        filepath: &str, capacity: usize
+    ) ->
+        Result<HighScores, Box<dyn Error>>
+    {
+        let mut highscores = HighScores::new(capacity);
+
+        if !Path::new(filepath).exists() {
+            return Ok(highscores);
+        }
+
+        let contents = fs::read_to_string(filepath)?;
+        let docs = YamlLoader::load_from_str(&contents)?;
+
+        let entries = match docs.first().and_then(Yaml::as_vec) {
+            Some(entries) => entries,
+            None => return Ok(highscores),
+        };
+
+        for entry in entries {
+            let field = |name: &str| {
+                format!("{}: high score entry without {}", filepath, name)
+            };
+
+            let score = entry["score"].as_i64()
+                .ok_or_else(|| field("score"))?;
+            let score = u32::try_from(score)
+                .map_err(|_| format!(
+                    "{}: high score {} out of range", filepath, score))?;
+
+            highscores.entries.push(HighScore {
+                name: entry["name"].as_str()
+                    .ok_or_else(|| field("name"))?.to_string(),
+                score: score,
+                date: entry["date"].as_str()
+                    .ok_or_else(|| field("date"))?.to_string(),
+                // seeds don't fit into YAML integers, so they're strings
+                seed: entry["seed"].as_str()
+                    .ok_or_else(|| field("seed"))?.parse()?,
+            });
+        }
+
+        highscores.entries.sort_by_key(|e| Reverse(e.score));
+        highscores.entries.truncate(capacity);
+
+        Ok(highscores)
+    }
+
+    pub fn save(
+        &self, filepath: &str
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        let mut entries = Vec::<Yaml>::new();
+
+        for entry in &(*self).entries {
+            let mut hash = Hash::new();
+
+            hash.insert(
+                Yaml::String("name".to_string()),
+                Yaml::String(entry.name.clone()));
+            hash.insert(
+                Yaml::String("score".to_string()),
+                Yaml::Integer(entry.score as i64));
+            hash.insert(
+                Yaml::String("date".to_string()),
+                Yaml::String(entry.date.clone()));
+            hash.insert(
+                Yaml::String("seed".to_string()),
+                Yaml::String(entry.seed.to_string()));
+
+            entries.push(Yaml::Hash(hash));
+        }
+
+        let mut contents = String::new();
+        YamlEmitter::new(&mut contents)
+            .dump(&Yaml::Array(entries))?;
+        contents.push('\n');
+
+        fs::write(filepath, contents)?;
+
+        Ok(())
+    }
+
+    pub fn entries(&self) -> &Vec<HighScore> {
+        &(*self).entries
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        if score == 0 {
+            return false;
+        }
+
+        (*self).entries.len() < (*self).capacity ||
+            (*self).entries.iter().any(|e| e.score < score)
+    }
+
+    pub fn insert(&mut self, entry: HighScore) {
+        // after the entries with the same score,
+        // the older ones keep their place
+        let position = (*self).entries.iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or((*self).entries.len());
+
+        (*self).entries.insert(position, entry);
+        (*self).entries.truncate((*self).capacity);
+    }
+}
+
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // days since 1970-01-01 to a civil date,
+    // see http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
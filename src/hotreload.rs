@@ -0,0 +1,82 @@
+use std::fs;
+use std::time::SystemTime;
+
+use crate::renderer::types::AssetError;
+use crate::renderer::types::Representation;
+
+// seconds between two looks at the files
+const POLL_TIME: f32 = 0.5;
+
+// keeps an eye on the files views were loaded from
+// and loads them again when they change
+pub struct Watcher {
+    // by view, when its file was last changed
+    modified: Vec<Option<SystemTime>>,
+    // seconds until the files are looked at again
+    poll: f32,
+}
+
+impl Watcher {
+    pub fn new(views: &[Representation]) -> Watcher {
+        Watcher {
+            modified: views.iter().map(modified).collect(),
+            poll: POLL_TIME,
+        }
+    }
+
+    // a file that can't be loaded is logged and the view
+    // stays as it was, so a half saved file doesn't end the game,
+    // returns the views reloaded and the files that failed
+    pub fn update(
+        &mut self, views: &mut [Representation], dt: f32
+    ) ->
+        Vec<Result<usize, AssetError>>
+    {
+        let mut reloads = Vec::<Result<usize, AssetError>>::new();
+
+        (*self).poll -= dt;
+
+        if (*self).poll > 0.0 {
+            return reloads;
+        }
+
+        (*self).poll = POLL_TIME;
+
+        for i in 0..views.len().min((*self).modified.len()) {
+            let modified = modified(&views[i]);
+
+            if modified == (*self).modified[i] {
+                continue;
+            }
+
+            (*self).modified[i] = modified;
+
+            let filepath = match views[i].source() {
+                Some(filepath) => filepath.to_string(),
+                None => continue,
+            };
+
+            match Representation::from_file(&filepath) {
+                Ok(view) => {
+                    log::info!("reloaded {}", filepath);
+                    views[i] = view;
+                    reloads.push(Ok(i));
+                }
+                Err(err) => {
+                    log::error!("{}", err);
+                    reloads.push(Err(err));
+                }
+            }
+        }
+
+        reloads
+    }
+}
+
+fn modified(view: &Representation) -> Option<SystemTime> {
+    let filepath = view.source()?;
+
+    fs::metadata(filepath)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -1,70 +1,313 @@
 mod renderer;
+mod assets;
+mod bindings;
+mod config;
+mod enemies;
 mod gameloop;
-
+mod highscores;
+mod hotreload;
+mod options;
+mod recording;
+mod starfield;
+mod viewer;
+mod waves;
+mod yaml;
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::env;
 use std::error::Error;
 use std::time::Duration;
+use std::mem;
 use std::panic;
+use std::process;
 use std::thread;
 
 use crossterm::input::InputEvent;
 use crossterm::input::KeyEvent;
+use crossterm::style::Color;
 use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use backtrace::Backtrace;
 
+use renderer::types::AnimationPlayer;
+use renderer::types::AssetError;
+use renderer::types::Backend;
+use renderer::types::Layer;
 use renderer::types::Location;
 use renderer::types::Renderer;
 use renderer::types::Representation;
-
-// screen size
-const S_SIZE: (u16, u16) = (50, 30);
+use renderer::types::Stats;
+use renderer::types::Style;
+use renderer::types::text_width;
+
+use bindings::Action;
+use bindings::Bindings;
+use config::Config;
+use enemies::Archetype;
+use enemies::Behavior;
+use gameloop::Clock;
+use gameloop::ManualClock;
+use gameloop::SystemClock;
+use gameloop::Timestep;
+use highscores::HighScore;
+use highscores::HighScores;
+use hotreload::Watcher;
+use options::Mode;
+use options::Options;
+use waves::Point;
+use waves::Spawn;
+use waves::Stage;
+use waves::Wave;
+use recording::Recorder;
+use recording::Replay;
+use starfield::Starfield;
+
+// columns right of the playfield taken by the wall, score and the rest
+const PANEL_WIDTH: u16 = 16;
 const FPS: u8 = 30;
 
+const BINDINGS_FILE: &str = "./res/config/bindings.yaml";
+const CONFIG_FILE: &str = "./res/config/game.yaml";
+const STAGES_DIR: &str = "./res/stages";
+const ENEMIES_FILE: &str = "./res/config/enemies.yaml";
+const OBJECTS_DIR: &str = "./res/objects";
+// object files loaded by name, with the animations played on them,
+// the game loads them from here and check-assets checks them
+const REQUIRED_OBJECTS: [(&str, &[&str]); 8] = [
+    ("turret.yaml", &["explode"]),
+    ("bullet.yaml", &[]),
+    ("beam.yaml", &[]),
+    ("captured.yaml", &[]),
+    ("shot.yaml", &[]),
+    ("explosion.yaml", &["explode"]),
+    ("debris.yaml", &["spin"]),
+    ("gameover.yaml", &[]),
+];
+// played on every enemy's view
+const ENEMY_ANIMATION: &str = "fly";
+// how far above the screen enemies come back from after a dive
+const ENEMY_RETURN_HEIGHT: f32 = 6.0;
+// seconds a boss keeps its tractor beam on
+const BEAM_TIME: f32 = 2.5;
+// cells per second of a freed turret flying back
+const FREED_SPEED: f32 = 12.0;
+
+// pieces flying off anything that blows up
+const DEBRIS_COUNT: usize = 6;
+const DEBRIS_TIME: f32 = 0.6;
+// seconds a score popup floats up for
+const POPUP_TIME: f32 = 0.8;
+
+const HIGHSCORES_FILE: &str = "highscores.yaml";
+const HIGHSCORES_COUNT: usize = 5;
+const NAME_LENGTH: usize = 8;
+// for a high score whose name wasn't entered before quitting
+const DEFAULT_NAME: &str = "PLAYER";
+
+enum EnemyMode {
+    // following the route, then heading to the slot
+    Entering,
+    InFormation,
+    // following the route down and off the screen
+    Diving,
+    // following the route to right above the turret
+    Capturing,
+    // holding still with the tractor beam on,
+    // for the given number of seconds
+    Beaming(f32),
+}
+
 struct Enemy {
     pub xf: f32,
     pub yf: f32,
+    // position before the last update, used to interpolate
+    pub prev_xf: f32,
+    pub prev_yf: f32,
     pub target_x: i32,
     pub target_y: i32,
     pub x: i32,
     pub y: i32,
-    pub speed: i32,
-    pub speedup: u8
+    // formation slot as (column, row)
+    pub slot: (i32, i32),
+    // index into the archetypes
+    pub kind: usize,
+    pub view: usize,
+    pub hit_points: u8,
+    // carries a captured turret
+    pub captive: bool,
+    pub mode: EnemyMode,
+    // waypoints still to fly through
+    pub route: VecDeque<Point>,
+    pub animation: AnimationPlayer,
+    // seconds until the enemy can fire again
+    pub reload: f32
 }
 
 struct Bullet {
     pub xf: f32,
     pub yf: f32,
+    pub prev_yf: f32,
     pub x: i32,
     pub y: i32,
     pub speed: i32,
 }
 
+// fired by enemies, flies in a straight line
+struct Shot {
+    pub xf: f32,
+    pub yf: f32,
+    pub prev_xf: f32,
+    pub prev_yf: f32,
+    // cells per second
+    pub velocity: Point,
+    pub x: i32,
+    pub y: i32,
+}
+
+// captured turret flying back to the player after its boss was shot
+struct FreedShip {
+    pub xf: f32,
+    pub yf: f32,
+    pub prev_xf: f32,
+    pub prev_yf: f32,
+}
+
+// only for show, gone after a while
+struct Effect {
+    pub xf: f32,
+    pub yf: f32,
+    pub prev_xf: f32,
+    pub prev_yf: f32,
+    // cells per second
+    pub velocity: Point,
+    // cells per second added to the downwards velocity every second
+    pub gravity: f32,
+    // seconds until it's gone
+    pub time_left: f32,
+    pub view: EffectView,
+    pub animation: AnimationPlayer,
+}
+
+enum EffectView {
+    // one of the loaded views
+    Loaded(usize),
+    // made for this effect alone, like score popups
+    Own(Representation),
+}
+
 struct Turret {
     pub x: i32,
     pub y: i32,
     pub view: usize,
     pub speed: i32,
+    pub animation: AnimationPlayer,
+}
+
+struct SettingsMenu {
+    // index into `Action::all()`
+    selected: usize,
+    // the next key pressed goes to the selected action
+    capturing: bool,
+    // why the last key couldn't be bound
+    error: Option<String>,
+}
+
+enum Screen {
+    Title,
+    Playing,
+    // game stays as it is until resumed
+    Paused,
+    GameOver,
+    // typing a name for a new high score
+    NameEntry(String),
+    Settings(SettingsMenu),
+    // asking whether to really quit,
+    // keeps the screen to go back to
+    ConfirmQuit(Box<Screen>),
 }
 
 struct GameState {
     views: Vec<Representation>,
+    // reloads views from their files, only in dev mode
+    watcher: Option<Watcher>,
+    starfield: Starfield,
     turret: Turret,
+    // a second turret is attached to the right of the first one
+    dual: bool,
+    freed: Option<FreedShip>,
+    captured_view: usize,
+    beam_view: usize,
     bullet_view: usize,
     bullets: Vec<Bullet>,
-    enemy_view: usize,
+    archetypes: Vec<Archetype>,
     enemies: Vec<Enemy>,
+    shot_view: usize,
+    shots: Vec<Shot>,
+    explosion_view: usize,
+    debris_view: usize,
+    effects: Vec<Effect>,
     ammo: u8,
-    score: u16,
-    gameover: bool,
+    score: u32,
+    lives: u8,
+    // seconds until the next turret shows up after losing one
+    respawn: f32,
+    // seconds the turret still blinks and can't be hit
+    invulnerable: f32,
+    config: Config,
+    // playfield size, the side panel comes on top
+    size: (u16, u16),
+    // the next frame has to be drawn in full
+    redraw: bool,
+    screen: Screen,
     gameover_view: usize,
-    started: bool,
-    restart: bool,
-    prestart_view: usize
+    prestart_view: usize,
+    paused_view: usize,
+    confirm_quit_view: usize,
+    // every random decision of the game comes from here,
+    // so the same seed plays out the same way
+    rng: StdRng,
+    // separate from the game's, so how much debris flies
+    // doesn't change how the game plays out
+    effects_rng: StdRng,
+    // what both were seeded with for the game being played
+    seed: u64,
+    highscores: HighScores,
+    // where to keep high scores, `None` when they
+    // shouldn't outlive the game (headless runs, replays)
+    highscores_path: Option<String>,
+    bindings: Bindings,
+    // where keys changed in the settings are kept,
+    // `None` for the same reasons as `highscores_path`
+    bindings_path: Option<String>,
+    stages: Vec<Stage>,
+    wave: Wave
+}
+
+fn main() {
+    // by the time an error gets here the terminal has been given
+    // back, so it's printed as is rather than debug formatted
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn run() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = Options::parse(&args)?;
+
+    if options.mode == Mode::CheckAssets {
+        return check_assets();
+    }
+
     simple_logging::log_to_file(
         "output.log", log::LevelFilter::Info)?;
     panic::set_hook(Box::new(|info| {
+        // the message below has to end up on a usable terminal
+        renderer::restore();
+
         let backtrace = Backtrace::new();
         let thread = thread::current();
         let thread = thread.name().unwrap_or("unnamed");
@@ -97,53 +340,265 @@ fn main() -> Result<(), Box<dyn Error>> {
                 )
             }
         }
+
+        eprintln!("the game crashed: {} (details in output.log)", msg);
     }));
 
-    let renderer =
-        renderer::init(S_SIZE.0 + 16, S_SIZE.1)?;
+    if let Mode::View(filepath) = &options.mode {
+        return viewer::view(filepath, FPS);
+    }
+
+    let mut replay = match &options.replay {
+        Some(filepath) => Some(Replay::load(filepath)?),
+        None => None,
+    };
+
+    // a replay only plays out the same with its own seed
+    let seed = match (options.seed, replay.as_ref().and_then(Replay::seed)) {
+        (Some(seed), _) => seed,
+        (None, Some(seed)) => seed,
+        (None, None) => rand::random::<u64>(),
+    };
+    log::info!("seed: {}", seed);
+
+    let rng = StdRng::seed_from_u64(seed);
+
+    let (highscores_path, bindings_path) =
+        if options.headless.is_some() || replay.is_some() {
+            (None, None)
+        } else {
+            (Some(HIGHSCORES_FILE.to_string()),
+                Some(BINDINGS_FILE.to_string()))
+        };
+
+    let highscores = match &highscores_path {
+        Some(filepath) =>
+            HighScores::load(filepath, HIGHSCORES_COUNT)?,
+        None =>
+            HighScores::new(HIGHSCORES_COUNT),
+    };
 
     let mut views = Vec::<Representation>::new();
 
-    let turret_view =
-        load_view(
-            "./res/objects/turret.yaml", &mut views)?;
-    let bullet_view =
-        load_view(
-            "./res/objects/bullet.yaml", &mut views)?;
-    let enemy_view =
-        load_view(
-            "./res/objects/enemy.yaml", &mut views)?;
-    let gameover_view =
-        load_view(
-            "./res/objects/gameover.yaml", &mut views)?;
-    let prestart_view =
-        load_view(
-            "./res/objects/prestart.yaml", &mut views)?;
+    // the same list check-assets goes by
+    let mut objects = HashMap::<&str, usize>::new();
+
+    for (filename, _) in REQUIRED_OBJECTS.iter() {
+        let view = load_view(
+            &format!("{}/{}", OBJECTS_DIR, filename), &mut views)?;
+
+        objects.insert(filename, view);
+    }
+
+    let turret_view = objects["turret.yaml"];
+    let bullet_view = objects["bullet.yaml"];
+    let beam_view = objects["beam.yaml"];
+    let captured_view = objects["captured.yaml"];
+    let shot_view = objects["shot.yaml"];
+    let explosion_view = objects["explosion.yaml"];
+    let debris_view = objects["debris.yaml"];
+    let gameover_view = objects["gameover.yaml"];
+
+    let bindings = Bindings::load(BINDINGS_FILE)?;
+    let mut config = Config::load(CONFIG_FILE)?;
+
+    if let Some(size) = options.size {
+        config::check_playfield(size)?;
+        config.playfield = size;
+    }
+
+    let size = config.playfield;
+
+    let mut starfield =
+        Starfield::new((size.0 - 3) as f32, size.1 as f32, seed);
+
+    for layer in config.starfield.iter() {
+        let view = load_view(
+            &format!("{}/{}", OBJECTS_DIR, layer.view), &mut views)?;
+
+        starfield.add_layer(&views, view, layer.density, layer.speed);
+    }
+
+    let archetypes =
+        enemies::load_archetypes(ENEMIES_FILE, OBJECTS_DIR, &mut views)?;
+    let kinds: Vec<&str> = archetypes.iter()
+        .map(|archetype| archetype.name.as_str())
+        .collect();
+
+    let stages = waves::load_stages(STAGES_DIR, &kinds)?;
+    let wave = Wave::new(&stages, 0, 0);
+
+    views.push(prestart_view(&bindings));
+    let prestart_view = views.len() - 1;
+    views.push(paused_view(&bindings));
+    let paused_view = views.len() - 1;
+    views.push(confirm_quit_view(&bindings));
+    let confirm_quit_view = views.len() - 1;
+
+    let watcher =
+        if options.dev {
+            Some(Watcher::new(&views))
+        } else {
+            None
+        };
 
     let state = GameState {
         views: views,
+        watcher: watcher,
+        starfield: starfield,
         turret: Turret {
             speed: 4,
-            x: S_SIZE.0 as i32 / 2 - 5,
-            y: S_SIZE.1 as i32 - 2,
-            view: turret_view
+            x: size.0 as i32 / 2 - 5,
+            y: size.1 as i32 - 2,
+            view: turret_view,
+            animation: AnimationPlayer::still()
         },
+        dual: false,
+        freed: None,
+        captured_view: captured_view,
+        beam_view: beam_view,
         bullet_view: bullet_view,
         bullets: Vec::<Bullet>::new(),
-        enemy_view: enemy_view,
-        enemies: create_enemies(),
+        archetypes: archetypes,
+        enemies: Vec::<Enemy>::new(),
+        shot_view: shot_view,
+        shots: Vec::<Shot>::new(),
+        explosion_view: explosion_view,
+        debris_view: debris_view,
+        effects: Vec::<Effect>::new(),
         ammo: 3,
         score: 0,
-        gameover: false,
+        lives: config.lives,
+        respawn: 0.0,
+        invulnerable: 0.0,
+        config: config,
+        size: size,
+        redraw: false,
+        screen: Screen::Title,
         gameover_view: gameover_view,
-        started: false,
-        restart: false,
-        prestart_view: prestart_view
+        prestart_view: prestart_view,
+        paused_view: paused_view,
+        confirm_quit_view: confirm_quit_view,
+        rng: rng,
+        effects_rng: StdRng::seed_from_u64(seed),
+        seed: seed,
+        highscores: highscores,
+        highscores_path: highscores_path,
+        bindings: bindings,
+        bindings_path: bindings_path,
+        stages: stages,
+        wave: wave
     };
 
+    let mut recorder = match &options.record {
+        Some(filepath) => Some(Recorder::create(filepath, seed)?),
+        None => None,
+    };
+
+    if let Some(frames) = options.headless {
+        let mut renderer =
+            renderer::headless(size.0 + PANEL_WIDTH, size.1);
+
+        match replay {
+            Some(mut replay) => {
+                replay.truncate(frames);
+                renderer.replay(replay);
+            }
+            None => {
+                renderer.backend_mut().script(
+                    0, InputEvent::Keyboard(KeyEvent::Enter));
+            }
+        }
+
+        renderer.backend_mut().script(
+            frames, InputEvent::Keyboard(KeyEvent::Ctrl('c')));
+
+        // headless runs have to be reproducible,
+        // so they never look at the wall clock
+        let mut clock = ManualClock::new();
+
+        gameloop::gameloop(
+            FPS, Timestep::Fixed(frame_duration()),
+            &mut clock, state, &mut renderer, recorder.as_mut(),
+            input, update, render)?;
+
+        log_stats(renderer.stats());
+
+        print!("{}", renderer.backend().snapshot());
+
+        return Ok(());
+    }
+
+    let mut renderer = renderer::init()?;
+
+    // recordings hold events per frame, so to play back
+    // the same way every frame has to be exactly one step
+    let (timestep, mut clock): (Timestep, Box<dyn Clock>) =
+        if recorder.is_some() || replay.is_some() {
+            (Timestep::Fixed(frame_duration()),
+                Box::new(ManualClock::paced()))
+        } else if options.fixed_step {
+            (Timestep::Fixed(frame_duration()),
+                Box::new(SystemClock::new()))
+        } else {
+            (Timestep::Variable,
+                Box::new(SystemClock::new()))
+        };
+
+    if let Some(replay) = replay.take() {
+        renderer.replay(replay);
+    }
+
     gameloop::gameloop(
-        FPS, state, renderer,
-        input, update, render)?;
+        FPS, timestep, &mut *clock, state, &mut renderer,
+        recorder.as_mut(), input, update, render)?;
+
+    log_stats(renderer.stats());
+
+    Ok(())
+}
+
+// how much output the game took, slow connections
+// like SSH show the first signs of too much
+fn log_stats(stats: &Stats) {
+    log::info!(
+        "frames: {}, bytes written: {} ({} per frame, {} at most)",
+        stats.frames,
+        stats.bytes,
+        stats.average_bytes(),
+        stats.max_bytes);
+}
+
+// report for everything wrong in res, for running before a commit
+fn check_assets() -> Result<(), Box<dyn Error>> {
+    let paths = assets::Paths {
+        objects_dir: OBJECTS_DIR,
+        bindings_file: BINDINGS_FILE,
+        config_file: CONFIG_FILE,
+        enemies_file: ENEMIES_FILE,
+        stages_dir: STAGES_DIR,
+    };
+    let requirements = assets::Requirements {
+        objects: &REQUIRED_OBJECTS,
+        enemy_animations: &[ENEMY_ANIMATION],
+    };
+
+    let report = assets::check(&paths, &requirements)?;
+
+    for warning in report.warnings.iter() {
+        println!("warning: {}", warning);
+    }
+    for error in report.errors.iter() {
+        println!("error: {}", error);
+    }
+
+    println!(
+        "{} object files checked: {} errors, {} warnings",
+        report.objects, report.errors.len(), report.warnings.len());
+
+    if !report.errors.is_empty() {
+        return Err("the assets have errors".into());
+    }
 
     Ok(())
 }
@@ -153,103 +608,423 @@ fn input(
     inputs: Vec<InputEvent>,
     proceed: &mut bool
 ) {
-    // *proceed = false;
-
     for event in inputs {
-        match event {
-            InputEvent::Keyboard(KeyEvent::Esc) => {
-                *proceed = false;
-                break;
+        let key = match event {
+            InputEvent::Keyboard(key) => key,
+            _ => continue,
+        };
+
+        // raw mode turns Ctrl-C into a key press,
+        // it still quits right away
+        if key == KeyEvent::Ctrl('c') {
+            save_unnamed_score(state);
+            *proceed = false;
+            break;
+        }
+
+        // for when something else has written over the game
+        if key == KeyEvent::Ctrl('l') {
+            (*state).redraw = true;
+            continue;
+        }
+
+        let action = (*state).bindings.action(&key);
+
+        match (*state).screen {
+            Screen::Title => match action {
+                Some(Action::Start) => reset_game(state),
+                Some(Action::Settings) => {
+                    (*state).screen = Screen::Settings(SettingsMenu {
+                        selected: 0,
+                        capturing: false,
+                        error: None,
+                    });
+                }
+                Some(Action::Quit) => confirm_quit(state),
+                _ => {}
+            },
+            Screen::Playing => match action {
+                Some(Action::Pause) => {
+                    (*state).screen = Screen::Paused;
+                }
+                Some(Action::Quit) => confirm_quit(state),
+                Some(action) => control_turret(state, action),
+                None => {}
+            },
+            Screen::Paused => match action {
+                Some(Action::Pause) | Some(Action::Start) => {
+                    (*state).screen = Screen::Playing;
+                }
+                Some(Action::Quit) => confirm_quit(state),
+                _ => {}
+            },
+            Screen::GameOver => match action {
+                Some(Action::Start) => reset_game(state),
+                Some(Action::Quit) => confirm_quit(state),
+                _ => {}
+            },
+            Screen::NameEntry(_) => {
+                // characters are part of the name
+                // even when they are bound to something
+                if
+                    action == Some(Action::Quit) &&
+                    !matches!(key, KeyEvent::Char(_))
+                {
+                    confirm_quit(state);
+                } else {
+                    enter_name(state, key);
+                }
             }
-            InputEvent::Keyboard(KeyEvent::Enter) => {
-                if !(*state).started {
-                    (*state).started = true;
+            Screen::Settings(_) => settings_input(state, key, action),
+            Screen::ConfirmQuit(_) => {
+                let yes =
+                    action == Some(Action::Start) ||
+                    key == KeyEvent::Char('y') ||
+                    key == KeyEvent::Char('Y');
+                let no =
+                    action == Some(Action::Quit) ||
+                    key == KeyEvent::Char('n') ||
+                    key == KeyEvent::Char('N');
+
+                if yes {
+                    save_unnamed_score(state);
+                    *proceed = false;
+                    break;
                 }
 
-                if (*state).gameover && !(*state).restart {
-                    (*state).gameover = false;
-                    (*state).restart = true;
+                if no {
+                    let screen = mem::replace(
+                        &mut (*state).screen, Screen::Title);
+
+                    if let Screen::ConfirmQuit(prev) = screen {
+                        (*state).screen = *prev;
+                    }
                 }
             }
-            InputEvent::Keyboard(KeyEvent::Right) => {
-                if !(*state).started || (*state).gameover {
-                    return;
-                }
+        }
+    }
+}
+
+// arrow keys pick an action and Enter waits for the key it gets
+// from then on, the menu keys aren't up to the bindings so
+// they can't be lost by binding something else to them
+fn settings_input(
+    state: &mut GameState,
+    key: KeyEvent,
+    action: Option<Action>
+) {
+    let actions = Action::all();
 
-                let speed = (*state).turret.speed;
+    let (selected, capturing) = match &(*state).screen {
+        Screen::Settings(menu) => (menu.selected, menu.capturing),
+        _ => return,
+    };
 
-                let turret_width = (*state).views[(*state).turret.view].data()[0].len() as i32;
+    if capturing {
+        let result = (*state).bindings.rebind(actions[selected], key);
 
-                if (*state).turret.x + speed + turret_width < S_SIZE.0 as i32 {
-                    (*state).turret.x += speed;
-                }
+        if result.is_ok() {
+            keys_changed(state);
+        }
+
+        (*state).screen = Screen::Settings(SettingsMenu {
+            selected: selected,
+            capturing: false,
+            error: result.err().map(|err| err.to_string()),
+        });
+        return;
+    }
+
+    let back =
+        key == KeyEvent::Esc ||
+        action == Some(Action::Quit) ||
+        action == Some(Action::Settings);
+
+    let selected = match key {
+        KeyEvent::Up => (selected + actions.len() - 1) % actions.len(),
+        KeyEvent::Down => (selected + 1) % actions.len(),
+        KeyEvent::Enter => selected,
+        _ if back => {
+            (*state).screen = Screen::Title;
+            return;
+        }
+        _ => return,
+    };
+
+    (*state).screen = Screen::Settings(SettingsMenu {
+        selected: selected,
+        capturing: key == KeyEvent::Enter,
+        error: None,
+    });
+}
+
+// everything listing keys shows the new ones,
+// and they're kept for the next game
+fn keys_changed(state: &mut GameState) {
+    (*state).views[(*state).prestart_view] =
+        prestart_view(&(*state).bindings);
+    (*state).views[(*state).paused_view] =
+        paused_view(&(*state).bindings);
+    (*state).views[(*state).confirm_quit_view] =
+        confirm_quit_view(&(*state).bindings);
+
+    if let Some(filepath) = &(*state).bindings_path {
+        if let Err(e) = (*state).bindings.save(filepath) {
+            log::error!(
+                "failed to save key bindings to {}: {}",
+                filepath, e);
+        }
+    }
+}
+
+fn confirm_quit(state: &mut GameState) {
+    let prev = mem::replace(&mut (*state).screen, Screen::Title);
+
+    (*state).screen = Screen::ConfirmQuit(Box::new(prev));
+}
+
+fn control_turret(
+    state: &mut GameState,
+    action: Action
+) {
+    // nothing to control until the next turret shows up
+    if (*state).respawn > 0.0 {
+        return;
+    }
+
+    match action {
+        Action::MoveRight => {
+            let speed = (*state).turret.speed;
+
+            let mut turret_width = (*state).views[(*state).turret.view].data()[0].len() as i32;
+
+            if (*state).dual {
+                turret_width *= 2;
             }
-            InputEvent::Keyboard(KeyEvent::Left) => {
-                if !(*state).started || (*state).gameover {
-                    return;
-                }
 
-                let speed = (*state).turret.speed;
+            if (*state).turret.x + speed + turret_width < (*state).size.0 as i32 {
+                (*state).turret.x += speed;
+            }
+        }
+        Action::MoveLeft => {
+            let speed = (*state).turret.speed;
 
-                if (*state).turret.x - speed >= 0 {
-                    (*state).turret.x -= speed;
-                }
+            if (*state).turret.x - speed >= 0 {
+                (*state).turret.x -= speed;
             }
-            InputEvent::Keyboard(KeyEvent::Up) => {
-                if !(*state).started || (*state).gameover {
-                    return;
+        }
+        Action::Fire if (*state).ammo > 0 => {
+            let turret_width = (*state).views[(*state).turret.view].data()[0].len() as i32;
+
+            // the dual fighter fires from both turrets
+            // as long as there's ammo for it
+            let ships = if (*state).dual { 2 } else { 1 };
+
+            for i in 0..ships {
+                if (*state).ammo == 0 {
+                    break;
                 }
 
-                if (*state).ammo > 0 {
-                    let x = (*state).turret.x + 3;
-                    let y = (*state).turret.y - 0;
+                let x = (*state).turret.x + 3 + i * turret_width;
+                let y = (*state).turret.y;
 
-                    (*state).ammo -= 1;
+                (*state).ammo -= 1;
 
-                    (*state).bullets.push(Bullet {
-                        speed: -20,
-                        x: x,
-                        y: y,
-                        xf: x as f32,
-                        yf: y as f32,
-                    });
-                }
+                (*state).bullets.push(Bullet {
+                    speed: -20,
+                    x: x,
+                    y: y,
+                    xf: x as f32,
+                    yf: y as f32,
+                    prev_yf: y as f32,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+fn enter_name(
+    state: &mut GameState,
+    key: KeyEvent
+) {
+    let name = match &mut (*state).screen {
+        Screen::NameEntry(name) => name,
+        _ => return,
+    };
+
+    match key {
+        KeyEvent::Char(c)
+            if c.is_ascii_alphanumeric() && name.len() < NAME_LENGTH =>
+        {
+            name.push(c.to_ascii_uppercase());
+        }
+        KeyEvent::Backspace => {
+            name.pop();
+        }
+        KeyEvent::Enter => {
+            if name.is_empty() {
+                return;
             }
-            _ => {}
+
+            let name = name.clone();
+            save_score(state, name);
+        }
+        _ => {}
+    }
+}
+
+// quitting while the name of a high score is being entered
+// keeps the score under what has been typed so far
+fn save_unnamed_score(state: &mut GameState) {
+    let screen = match &(*state).screen {
+        Screen::ConfirmQuit(prev) => &**prev,
+        screen => screen,
+    };
+
+    let name = match screen {
+        Screen::NameEntry(name) if name.is_empty() =>
+            DEFAULT_NAME.to_string(),
+        Screen::NameEntry(name) => name.clone(),
+        _ => return,
+    };
+
+    save_score(state, name);
+}
+
+fn save_score(state: &mut GameState, name: String) {
+    let entry = HighScore {
+        name: name,
+        score: (*state).score,
+        date: highscores::today(),
+        seed: (*state).seed,
+    };
+
+    (*state).highscores.insert(entry);
+    (*state).screen = Screen::GameOver;
+
+    if let Some(filepath) = &(*state).highscores_path {
+        if let Err(e) = (*state).highscores.save(filepath) {
+            log::error!(
+                "failed to save high scores to {}: {}",
+                filepath, e);
         }
     }
 }
 
+fn reset_game(state: &mut GameState) {
+    // every game after the first gets a seed of its own, drawn
+    // from the last game's so a replay still plays out the same
+    if matches!((*state).screen, Screen::GameOver) {
+        (*state).seed = (*state).rng.gen();
+        log::info!("seed: {}", (*state).seed);
+    }
+
+    (*state).rng = StdRng::seed_from_u64((*state).seed);
+    (*state).effects_rng = StdRng::seed_from_u64((*state).seed);
+    (*state).turret = Turret {
+                speed: 4,
+                x: (*state).size.0 as i32 / 2 - 5,
+                y: (*state).size.1 as i32 - 2,
+                view: (*state).turret.view,
+                animation: AnimationPlayer::still()
+    };
+    (*state).dual = false;
+    (*state).freed = None;
+    (*state).bullets = Vec::<Bullet>::new();
+    (*state).enemies = Vec::<Enemy>::new();
+    (*state).shots = Vec::<Shot>::new();
+    (*state).effects = Vec::<Effect>::new();
+    (*state).wave = Wave::new(&(*state).stages, 0, 0);
+    (*state).ammo = 3;
+    (*state).score = 0;
+    (*state).lives = (*state).config.lives;
+    (*state).respawn = 0.0;
+    (*state).invulnerable = 0.0;
+    (*state).screen = Screen::Playing;
+}
+
 fn update(
     state: &mut GameState,
     delta: Duration
 ) {
-    if (*state).restart {
-        (*state).turret = Turret {
-                    speed: 4,
-                    x: S_SIZE.0 as i32 / 2 - 5,
-                    y: S_SIZE.1 as i32 - 2,
-                    view: (*state).turret.view
-        };
-        (*state).bullet_view = (*state).bullet_view;
-        (*state).bullets = Vec::<Bullet>::new();
-        (*state).enemy_view = (*state).enemy_view;
-        (*state).enemies = create_enemies();
-        (*state).ammo = 3;
-        (*state).score = 0;
-        (*state).gameover = false;
-        (*state).gameover_view = (*state).gameover_view;
-        (*state).started = true;
-        (*state).restart = false;
-        (*state).prestart_view = (*state).prestart_view;
-    }
-
-    if (*state).gameover || !(*state).started {
+    let dt = delta.as_secs_f32();
+
+    // files are looked at on every screen, paused included,
+    // so changes can be seen without playing on
+    if let Some(watcher) = (*state).watcher.as_mut() {
+        watcher.update(&mut (*state).views, dt);
+    }
+
+    // the last turret still blows up under the game over screen
+    if matches!((*state).screen, Screen::GameOver | Screen::NameEntry(_)) {
+        (*state).turret.animation.update(
+            &(*state).views[(*state).turret.view], dt);
+        update_effects(state, dt);
         return;
     }
 
-    let mut enemies_on_removal = Vec::<usize>::new();
-    let mut new_enemies = Vec::<Enemy>::new();
+    if !matches!((*state).screen, Screen::Playing) {
+        return;
+    }
+
+    (*state).starfield.update(&(*state).views, dt);
+
+    if (*state).respawn > 0.0 {
+        (*state).respawn -= dt;
+
+        if (*state).respawn <= 0.0 {
+            (*state).turret.x = (*state).size.0 as i32 / 2 - 5;
+            (*state).turret.animation.stop();
+            (*state).invulnerable = (*state).config.invulnerable_time;
+        }
+    } else if (*state).invulnerable > 0.0 {
+        (*state).invulnerable = ((*state).invulnerable - dt).max(0.0);
+    }
+
+    (*state).turret.animation.update(
+        &(*state).views[(*state).turret.view], dt);
+
+    (*state).wave.time += dt;
+
+    while let Some(spawn) = (*state).wave.pending.front() {
+        if spawn.at > (*state).wave.time {
+            break;
+        }
+
+        if let Some(spawn) = (*state).wave.pending.pop_front() {
+            let archetype = &(*state).archetypes[spawn.kind];
+            let view = &(*state).views[archetype.view];
+
+            // where the enemy's top left corner can be
+            // without leaving the playfield
+            let room = (
+                ((*state).size.0 - 3) as f32 - view.data()[0].len() as f32,
+                (*state).size.1 as f32 - view.data().len() as f32,
+            );
+
+            (*state).enemies.push(spawn_enemy(spawn, archetype, room));
+        }
+    }
+
+    // dives only start once the whole wave is in
+    if (*state).wave.pending.is_empty() {
+        (*state).wave.dive_timer -= dt;
+
+        if (*state).wave.dive_timer <= 0.0 {
+            (*state).wave.dive_timer =
+                (*state).stages[(*state).wave.stage].dive_interval;
+
+            start_dive(state);
+        }
+    }
+
+    let speed = (*state).wave.speed(&(*state).stages);
+
+    // formations are laid out for the narrowest playfield
+    // and kept in the middle of wider ones
+    let shift =
+        ((*state).size.0 - config::MIN_PLAYFIELD.0) as f32 / 2.0;
 
     for i in 0..(*state).enemies.len() {
         let enemy_ptr = &mut (*state).enemies[i];
@@ -257,24 +1032,77 @@ fn update(
         (*enemy_ptr).target_x = (*state).turret.x;
         (*enemy_ptr).target_y = (*state).turret.y;
 
-        (*enemy_ptr).yf +=
-            (*enemy_ptr).speed as f32 * delta.as_secs_f32();
+        (*enemy_ptr).prev_xf = (*enemy_ptr).xf;
+        (*enemy_ptr).prev_yf = (*enemy_ptr).yf;
+
+        if let EnemyMode::Beaming(time) = &mut (*enemy_ptr).mode {
+            *time -= dt;
+
+            // nothing caught, back to the formation
+            if *time <= 0.0 {
+                (*enemy_ptr).mode = EnemyMode::Entering;
+            }
+        }
+
+        let mut slot = (*state).wave.slot_position(
+            &(*state).stages, (*enemy_ptr).slot);
+        slot.0 += shift;
+
+        let enemy_speed =
+            speed * (*state).archetypes[(*enemy_ptr).kind].speed;
 
+        fly_enemy(enemy_ptr, slot, enemy_speed * dt);
+
+        (*enemy_ptr).x = (*enemy_ptr).xf.round() as i32;
         (*enemy_ptr).y = (*enemy_ptr).yf.round() as i32;
 
+        (*enemy_ptr).reload -= dt;
+
+        (*enemy_ptr).animation.update(
+            &(*state).views[(*enemy_ptr).view], dt);
+    }
+
+    fire_shots(state);
+
+    let captured = capture_turret(state);
+
+    fly_freed_ship(state, dt);
+
+    let mut shots_on_removal = Vec::<usize>::new();
+
+    for i in 0..(*state).shots.len() {
+        let shot_ptr = &mut (*state).shots[i];
+
+        (*shot_ptr).prev_xf = (*shot_ptr).xf;
+        (*shot_ptr).prev_yf = (*shot_ptr).yf;
+
+        (*shot_ptr).xf += (*shot_ptr).velocity.0 * dt;
+        (*shot_ptr).yf += (*shot_ptr).velocity.1 * dt;
+
+        (*shot_ptr).x = (*shot_ptr).xf.round() as i32;
+        (*shot_ptr).y = (*shot_ptr).yf.round() as i32;
+
         if
-            (*enemy_ptr).y < 0 ||
-            (*enemy_ptr).y > S_SIZE.1 as i32
+            (*shot_ptr).x < 0 ||
+            (*shot_ptr).x >= (*state).size.0 as i32 - 3 ||
+            (*shot_ptr).y > (*state).size.1 as i32
         {
-            enemies_on_removal.push(i);
+            shots_on_removal.push(i);
         }
     }
 
+    let mut enemies_on_removal = Vec::<usize>::new();
+    // scores to pop up where they were made
+    let mut popups = Vec::<(Point, u32)>::new();
+    // where enemies got hit without going down
+    let mut sparks = Vec::<Point>::new();
+
 
 
     let mut bullets_on_removal = Vec::<usize>::new();
 
     for i in 0..(*state).bullets.len() {
+        (*state).bullets[i].prev_yf = (*state).bullets[i].yf;
         (*state).bullets[i].yf +=
             (*state).bullets[i].speed as f32 * delta.as_secs_f32();
 
@@ -282,7 +1110,7 @@ fn update(
 
         if
             (*state).bullets[i].y < 0 ||
-            (*state).bullets[i].y > S_SIZE.1 as i32
+            (*state).bullets[i].y > (*state).size.1 as i32
         {
             bullets_on_removal.push(i);
         }
@@ -293,50 +1121,125 @@ fn update(
     let bullet_width = (*state).views[(*state).bullet_view].data()[0].len() as f32;
     let bullet_height = (*state).views[(*state).bullet_view].data().len() as f32;
 
-    let enemy_width = (*state).views[(*state).enemy_view].data()[0].len() as f32;
-    let enemy_height = (*state).views[(*state).enemy_view].data().len() as f32;
-
     for i in 0..(*state).bullets.len() {
         let bullet_ptr = &mut (*state).bullets[i];
-        let bullet_delta = (*bullet_ptr).speed as f32 * delta.as_secs_f32();
 
         for j in 0..(*state).enemies.len() {
             let enemy_ptr = &mut (*state).enemies[j];
-            let enemy_delta = (*enemy_ptr).speed as f32 * delta.as_secs_f32();
+            let enemy_width = (*state).views[(*enemy_ptr).view].data()[0].len() as f32;
+            let enemy_height = (*state).views[(*enemy_ptr).view].data().len() as f32;
+
+            // already shot down during this update
+            if (*enemy_ptr).hit_points == 0 {
+                continue;
+            }
+
+            // everything the enemy covered during this update
+            let enemy_left = (*enemy_ptr).xf.min((*enemy_ptr).prev_xf);
+            let enemy_right =
+                (*enemy_ptr).xf.max((*enemy_ptr).prev_xf) + enemy_width;
+            let enemy_top = (*enemy_ptr).yf.min((*enemy_ptr).prev_yf);
+            let enemy_bottom =
+                (*enemy_ptr).yf.max((*enemy_ptr).prev_yf) + enemy_height;
 
             if
-                (*enemy_ptr).xf <= (*bullet_ptr).xf + bullet_width &&
-                (*bullet_ptr).xf <= (*enemy_ptr).xf + enemy_width &&
+                enemy_left <= (*bullet_ptr).xf + bullet_width &&
+                (*bullet_ptr).xf <= enemy_right &&
 
-                (*enemy_ptr).yf - enemy_delta <= (*bullet_ptr).yf + bullet_height - bullet_delta &&
-                (*bullet_ptr).yf <= (*enemy_ptr).yf + enemy_height
+                enemy_top <= (*bullet_ptr).prev_yf + bullet_height &&
+                (*bullet_ptr).yf <= enemy_bottom
             {
+                let archetype = &(*state).archetypes[(*enemy_ptr).kind];
+
                 bullets_on_removal.push(i);
+                (*enemy_ptr).hit_points -= 1;
+
+                if (*enemy_ptr).hit_points > 0 {
+                    (*enemy_ptr).view = archetype.damaged_view;
+                    sparks.push((
+                        (*bullet_ptr).xf,
+                        (*enemy_ptr).yf + enemy_height,
+                    ));
+                    break;
+                }
+
                 enemies_on_removal.push(j);
-                (*enemy_ptr).speedup = 1;
-                (*state).score += 1;
+
+                let score = match (*enemy_ptr).mode {
+                    EnemyMode::InFormation | EnemyMode::Entering =>
+                        archetype.formation_score,
+                    _ => archetype.diving_score,
+                };
+                (*state).score = (*state).score.saturating_add(score);
+                popups.push((
+                    (
+                        (*enemy_ptr).xf + enemy_width / 2.0,
+                        (*enemy_ptr).yf + enemy_height / 2.0,
+                    ),
+                    score,
+                ));
+
+                // a bullet only takes down one enemy
+                break;
             }
         }
     }
 
 
-    {
+    // which of the turrets got hit, the left one is 0
+    let mut hit_ship: Option<i32> = None;
+
+    // a turret that's gone or just came back can't be hit
+    if (*state).respawn <= 0.0 && (*state).invulnerable <= 0.0 {
         let turret_ptr = &mut (*state).turret;
-        let turret_width = (*state).views[(*turret_ptr).view].data()[0].len() as i32 - 2;
+        let turret_full_width = (*state).views[(*turret_ptr).view].data()[0].len() as i32;
+        let turret_width = turret_full_width - 2;
         let turret_height = (*state).views[(*turret_ptr).view].data().len() as i32;
-        for i in 0..(*state).enemies.len() {
-            let enemy_ptr = &mut (*state).enemies[i];
-            let enemy_width = (*state).views[(*state).enemy_view].data()[0].len() as i32;
-            let enemy_height = (*state).views[(*state).enemy_view].data().len() as i32;
 
-            if
-                (*turret_ptr).x + 1 + turret_width > (*enemy_ptr).x &&
-                (*enemy_ptr).x + enemy_width > (*turret_ptr).x + 1 &&
+        let ships = if (*state).dual { 2 } else { 1 };
 
-                (*turret_ptr).y + turret_height > (*enemy_ptr).y &&
-                (*enemy_ptr).y + enemy_height > (*turret_ptr).y
-            {
-                (*state).gameover = true;
+        for ship in 0..ships {
+            let turret_x = (*turret_ptr).x + ship * turret_full_width;
+
+            for i in 0..(*state).enemies.len() {
+                let enemy_ptr = &mut (*state).enemies[i];
+                let enemy_width = (*state).views[(*enemy_ptr).view].data()[0].len() as i32;
+                let enemy_height = (*state).views[(*enemy_ptr).view].data().len() as i32;
+
+                if
+                    turret_x + 1 + turret_width > (*enemy_ptr).x &&
+                    (*enemy_ptr).x + enemy_width > turret_x + 1 &&
+
+                    (*turret_ptr).y + turret_height > (*enemy_ptr).y &&
+                    (*enemy_ptr).y + enemy_height > (*turret_ptr).y
+                {
+                    hit_ship = Some(ship);
+                    enemies_on_removal.push(i);
+                }
+            }
+
+            let shot_width = (*state).views[(*state).shot_view].data()[0].len() as f32;
+            let shot_height = (*state).views[(*state).shot_view].data().len() as f32;
+            for i in 0..(*state).shots.len() {
+                let shot_ptr = &(*state).shots[i];
+
+                // shots are fast and small, so everything
+                // they crossed during this update counts
+                let shot_left = (*shot_ptr).xf.min((*shot_ptr).prev_xf);
+                let shot_right = (*shot_ptr).xf.max((*shot_ptr).prev_xf) + shot_width;
+                let shot_top = (*shot_ptr).yf.min((*shot_ptr).prev_yf);
+                let shot_bottom = (*shot_ptr).yf.max((*shot_ptr).prev_yf) + shot_height;
+
+                if
+                    (turret_x + 1 + turret_width) as f32 > shot_left &&
+                    shot_right > (turret_x + 1) as f32 &&
+
+                    ((*turret_ptr).y + turret_height) as f32 > shot_top &&
+                    shot_bottom > (*turret_ptr).y as f32
+                {
+                    hit_ship = Some(ship);
+                    shots_on_removal.push(i);
+                }
             }
         }
     }
@@ -351,71 +1254,644 @@ fn update(
         (*state).ammo += 1;
     }
 
+    shots_on_removal.sort();
+    shots_on_removal.reverse();
+    shots_on_removal.dedup();
+    for i in shots_on_removal {
+        (*state).shots.remove(i);
+    }
+
     enemies_on_removal.sort();
     enemies_on_removal.reverse();
     enemies_on_removal.dedup();
     for i in enemies_on_removal.iter().cloned() {
         let removed_enemy = (*state).enemies.remove(i);
-        new_enemies.push(
-            create_enemy(
-                removed_enemy.speed + removed_enemy.speedup as i32, 0
-            )
+
+        let removed_view = &(*state).views[removed_enemy.view];
+        let center = (
+            removed_enemy.xf + removed_view.data()[0].len() as f32 / 2.0,
+            removed_enemy.yf + removed_view.data().len() as f32 / 2.0,
         );
+        explode(state, center, DEBRIS_COUNT);
+
+        // shooting the boss sets its captive free
+        if removed_enemy.captive {
+            (*state).freed = Some(FreedShip {
+                xf: removed_enemy.xf + 1.0,
+                yf: removed_enemy.yf - 2.0,
+                prev_xf: removed_enemy.xf + 1.0,
+                prev_yf: removed_enemy.yf - 2.0,
+            });
+        }
+    }
+
+    for (position, score) in popups {
+        score_popup(state, position, score);
+    }
+
+    for position in sparks {
+        spray_debris(state, position, DEBRIS_COUNT / 2);
+    }
+
+    if let Some(ship) = hit_ship {
+        let turret_view = &(*state).views[(*state).turret.view];
+        let turret_width = turret_view.data()[0].len() as f32;
+
+        let center = (
+            (*state).turret.x as f32 + turret_width * (ship as f32 + 0.5),
+            (*state).turret.y as f32 + turret_view.data().len() as f32 / 2.0,
+        );
+        explode(state, center, DEBRIS_COUNT * 2);
+    }
+
+    update_effects(state, dt);
+
+    match hit_ship {
+        // the dual fighter just loses one of its turrets
+        Some(ship) if (*state).dual => {
+            let turret_width = (*state).views[(*state).turret.view].data()[0].len() as i32;
+
+            if ship == 0 {
+                (*state).turret.x += turret_width;
+            }
+
+            (*state).dual = false;
+        }
+        Some(_) => {
+            (*state).turret.animation.play("explode", false);
+            lose_life(state);
+        }
+        // a captured turret is just gone
+        None if captured => lose_life(state),
+        None => {}
+    }
+
+    if (*state).enemies.is_empty() && (*state).wave.pending.is_empty() {
+        (*state).wave = (*state).wave.next(&(*state).stages);
+    }
+}
+
+// explosion and debris centered on `center`
+fn explode(state: &mut GameState, center: Point, debris: usize) {
+    let view = &(*state).views[(*state).explosion_view];
+
+    let x = center.0 - view.data()[0].len() as f32 / 2.0;
+    let y = center.1 - view.data().len() as f32 / 2.0;
+
+    // the explosion is over when its animation is
+    let time = view.animation("explode")
+        .map(|animation| animation.durations().iter().sum())
+        .unwrap_or(0.0);
+
+    (*state).effects.push(Effect {
+        xf: x,
+        yf: y,
+        prev_xf: x,
+        prev_yf: y,
+        velocity: (0.0, 0.0),
+        gravity: 0.0,
+        time_left: time,
+        view: EffectView::Loaded((*state).explosion_view),
+        animation: AnimationPlayer::playing("explode", false),
+    });
+
+    spray_debris(state, center, debris);
+}
+
+fn spray_debris(state: &mut GameState, center: Point, count: usize) {
+    for _i in 0..count {
+        let angle = (*state).effects_rng
+            .gen_range(0.0, std::f32::consts::PI * 2.0);
+        let speed = (*state).effects_rng.gen_range(6.0, 14.0);
+
+        (*state).effects.push(Effect {
+            xf: center.0,
+            yf: center.1,
+            prev_xf: center.0,
+            prev_yf: center.1,
+            // cells are about twice as high as they are wide
+            velocity: (angle.cos() * speed, angle.sin() * speed / 2.0),
+            gravity: 12.0,
+            time_left: DEBRIS_TIME,
+            view: EffectView::Loaded((*state).debris_view),
+            animation: AnimationPlayer::playing("spin", true),
+        });
+    }
+}
+
+fn score_popup(state: &mut GameState, center: Point, score: u32) {
+    let view = text_view(
+        &[format!("+{}", score)],
+        Style { bold: true, ..Style::fg(Color::White) });
+
+    let x = center.0 - view.data()[0].len() as f32 / 2.0;
+
+    (*state).effects.push(Effect {
+        xf: x,
+        yf: center.1,
+        prev_xf: x,
+        prev_yf: center.1,
+        velocity: (0.0, -3.0),
+        gravity: 0.0,
+        time_left: POPUP_TIME,
+        view: EffectView::Own(view),
+        animation: AnimationPlayer::still(),
+    });
+}
+
+fn update_effects(state: &mut GameState, dt: f32) {
+    for i in 0..(*state).effects.len() {
+        let effect_ptr = &mut (*state).effects[i];
+
+        (*effect_ptr).prev_xf = (*effect_ptr).xf;
+        (*effect_ptr).prev_yf = (*effect_ptr).yf;
+
+        (*effect_ptr).velocity.1 += (*effect_ptr).gravity * dt;
+
+        (*effect_ptr).xf += (*effect_ptr).velocity.0 * dt;
+        (*effect_ptr).yf += (*effect_ptr).velocity.1 * dt;
+
+        (*effect_ptr).time_left -= dt;
+
+        let view = effect_view(&(*state).views, &(*effect_ptr).view);
+        (*effect_ptr).animation.update(view, dt);
+    }
+
+    // nothing flies over the wall into the side panel
+    let wall = ((*state).size.0 - 3) as f32;
+
+    (*state).effects.retain(|effect| {
+        effect.time_left > 0.0 &&
+        effect.xf >= 0.0 &&
+        effect.xf < wall
+    });
+}
+
+fn effect_view<'a>(
+    views: &'a [Representation], view: &'a EffectView
+) -> &'a Representation {
+    match view {
+        EffectView::Loaded(view) => &views[*view],
+        EffectView::Own(view) => view,
+    }
+}
+
+fn lose_life(state: &mut GameState) {
+    (*state).lives -= 1;
+    (*state).shots = Vec::<Shot>::new();
+
+    if (*state).lives > 0 {
+        (*state).respawn = (*state).config.respawn_time;
+        return;
+    }
+
+    (*state).screen =
+        if (*state).highscores.qualifies((*state).score) {
+            Screen::NameEntry(String::new())
+        } else {
+            Screen::GameOver
+        };
+}
+
+// paths go across `room`, from 0 at its top left to 1 at its bottom right
+fn spawn_enemy(spawn: Spawn, archetype: &Archetype, room: Point) -> Enemy {
+    let mut route: VecDeque<Point> = spawn.path.into_iter()
+        .map(|(x, y)| (x * room.0, y * room.1))
+        .collect();
+    let (x, y) = route.pop_front().unwrap_or((0.0, 0.0));
+
+    Enemy {
+        xf: x,
+        yf: y,
+        prev_xf: x,
+        prev_yf: y,
+        target_x: x as i32,
+        target_y: y as i32,
+        x: x as i32,
+        y: y as i32,
+        slot: spawn.slot,
+        kind: spawn.kind,
+        view: archetype.view,
+        hit_points: archetype.hit_points,
+        captive: false,
+        mode: EnemyMode::Entering,
+        route: route,
+        animation: AnimationPlayer::playing(ENEMY_ANIMATION, true),
+        reload: 0.0
+    }
+}
+
+// diving enemies fire at where the turret was
+// when they last looked
+fn fire_shots(state: &mut GameState) {
+    let stage = &(*state).stages[(*state).wave.stage];
+
+    let turret_width =
+        (*state).views[(*state).turret.view].data()[0].len() as f32;
+
+    for i in 0..(*state).enemies.len() {
+        let enemy_ptr = &mut (*state).enemies[i];
+
+        let enemy_view = &(*state).views[(*enemy_ptr).view];
+        let enemy_width = enemy_view.data()[0].len() as f32;
+        let enemy_height = enemy_view.data().len() as f32;
+
+        if
+            !matches!((*enemy_ptr).mode, EnemyMode::Diving) ||
+            (*enemy_ptr).reload > 0.0
+        {
+            continue;
+        }
+
+        (*enemy_ptr).reload = stage.shot_interval;
+
+        let from = (
+            (*enemy_ptr).xf + enemy_width / 2.0,
+            (*enemy_ptr).yf + enemy_height,
+        );
+        let to = (
+            (*enemy_ptr).target_x as f32 + turret_width / 2.0,
+            (*enemy_ptr).target_y as f32,
+        );
+
+        // only downwards, an enemy below the turret holds fire
+        if to.1 <= from.1 {
+            continue;
+        }
+
+        let dx = to.0 - from.0;
+        let dy = to.1 - from.1;
+        let length = (dx * dx + dy * dy).sqrt();
+
+        (*state).shots.push(Shot {
+            xf: from.0,
+            yf: from.1,
+            prev_xf: from.0,
+            prev_yf: from.1,
+            velocity: (
+                dx / length * stage.shot_speed,
+                dy / length * stage.shot_speed,
+            ),
+            x: from.0.round() as i32,
+            y: from.1.round() as i32,
+        });
+    }
+}
+
+// sends a random enemy from the formation at the turret
+fn start_dive(state: &mut GameState) {
+    let candidates: Vec<usize> = (0..(*state).enemies.len())
+        .filter(|i| matches!(
+            (*state).enemies[*i].mode, EnemyMode::InFormation))
+        .collect();
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    let i = candidates[(*state).rng.gen_range(0, candidates.len())];
+
+    // only one turret can be held captive at a time
+    let behavior = (*state).archetypes[(*state).enemies[i].kind].behavior;
+
+    let can_capture =
+        behavior == Behavior::Capture &&
+        !(*state).dual &&
+        (*state).freed.is_none() &&
+        !(*state).enemies.iter().any(|e| e.captive);
+
+    if can_capture && (*state).rng.gen_bool(0.5) {
+        start_capture(state, i);
+        return;
     }
 
-    while let Some(new_enemy) = new_enemies.pop() {
-        (*state).enemies.push(new_enemy);
+    let enemy_ptr = &mut (*state).enemies[i];
+
+    let (x, y) = ((*enemy_ptr).xf, (*enemy_ptr).yf);
+    let aim = (*state).turret.x as f32 - 1.0;
+
+    // loop out to the side away from the turret first
+    let side = if x < aim { -1.0 } else { 1.0 };
+
+    let turret_y = (*state).turret.y as f32;
+
+    let route = match behavior {
+        Behavior::Swoop => vec![
+            (x + side * 4.0, y - 2.0),
+            (x + side * 2.0, y + 6.0),
+            (aim - side * 8.0, turret_y - 14.0),
+            (aim + side * 6.0, turret_y - 7.0),
+            (aim - side * 4.0, (*state).size.1 as f32 + 2.0),
+        ],
+        Behavior::Dive | Behavior::Capture => vec![
+            (x + side * 4.0, y - 2.0),
+            (x + side * 6.0, y + 3.0),
+            (aim, turret_y - 10.0),
+            (aim - side * 10.0, (*state).size.1 as f32 + 2.0),
+        ],
+    };
+
+    (*enemy_ptr).route = route.into_iter().collect();
+    (*enemy_ptr).mode = EnemyMode::Diving;
+    // first shot once the loop is done
+    (*enemy_ptr).reload =
+        (*state).stages[(*state).wave.stage].shot_interval / 2.0;
+}
+
+// flies a boss to right above the turret to switch its beam on
+fn start_capture(state: &mut GameState, i: usize) {
+    let enemy_view = &(*state).views[(*state).enemies[i].view];
+    let enemy_width = enemy_view.data()[0].len() as f32;
+    let enemy_height = enemy_view.data().len() as f32;
+
+    let turret_width =
+        (*state).views[(*state).turret.view].data()[0].len() as f32;
+    let beam_height =
+        (*state).views[(*state).beam_view].data().len() as f32;
+
+    let enemy_ptr = &mut (*state).enemies[i];
+
+    let (x, y) = ((*enemy_ptr).xf, (*enemy_ptr).yf);
+
+    (*enemy_ptr).route = vec![
+        (x, y - 2.0),
+        (
+            (*state).turret.x as f32 + turret_width / 2.0 - enemy_width / 2.0,
+            (*state).turret.y as f32 - beam_height - enemy_height,
+        ),
+    ].into_iter().collect();
+    (*enemy_ptr).mode = EnemyMode::Capturing;
+}
+
+// a turret under a beam is taken away by the boss,
+// returns whether that happened
+fn capture_turret(state: &mut GameState) -> bool {
+    if
+        (*state).dual ||
+        (*state).respawn > 0.0 ||
+        (*state).invulnerable > 0.0
+    {
+        return false;
+    }
+
+    let beam_width =
+        (*state).views[(*state).beam_view].data()[0].len() as f32;
+    let turret_width =
+        (*state).views[(*state).turret.view].data()[0].len() as f32;
+    let turret_center = (*state).turret.x as f32 + turret_width / 2.0;
+
+    for i in 0..(*state).enemies.len() {
+        let enemy_width =
+            (*state).views[(*state).enemies[i].view].data()[0].len() as f32;
+        let enemy_ptr = &mut (*state).enemies[i];
+
+        if !matches!((*enemy_ptr).mode, EnemyMode::Beaming(_)) {
+            continue;
+        }
+
+        let beam_left = (*enemy_ptr).xf + enemy_width / 2.0 - beam_width / 2.0;
+
+        // the beam's outer columns only just touch the turret
+        if
+            beam_left + 1.0 <= turret_center &&
+            turret_center <= beam_left + beam_width - 1.0
+        {
+            (*enemy_ptr).captive = true;
+            (*enemy_ptr).mode = EnemyMode::Entering;
+            return true;
+        }
+    }
+
+    false
+}
+
+// a freed turret docks to the right of the player's one,
+// waiting while there's none to dock to
+fn fly_freed_ship(state: &mut GameState, dt: f32) {
+    let turret_width =
+        (*state).views[(*state).turret.view].data()[0].len() as i32;
+
+    let freed_ptr = match &mut (*state).freed {
+        Some(freed) => freed,
+        None => return,
+    };
+
+    (*freed_ptr).prev_xf = (*freed_ptr).xf;
+    (*freed_ptr).prev_yf = (*freed_ptr).yf;
+
+    if (*state).respawn > 0.0 {
+        return;
+    }
+
+    let target = (
+        ((*state).turret.x + turret_width) as f32,
+        (*state).turret.y as f32,
+    );
+    let mut pos = ((*freed_ptr).xf, (*freed_ptr).yf);
+
+    let docked = waves::fly_towards(&mut pos, target, FREED_SPEED * dt);
+
+    (*freed_ptr).xf = pos.0;
+    (*freed_ptr).yf = pos.1;
+
+    if docked {
+        (*state).freed = None;
+        (*state).dual = true;
+
+        // both turrets have to fit on the screen
+        let limit = (*state).size.0 as i32 - 2 * turret_width - 1;
+        if (*state).turret.x > limit {
+            (*state).turret.x = limit;
+        }
+    }
+}
+
+fn fly_enemy(enemy: &mut Enemy, slot: Point, dist: f32) {
+    let mut pos = ((*enemy).xf, (*enemy).yf);
+
+    match (*enemy).mode {
+        EnemyMode::InFormation => {
+            pos = slot;
+        }
+        EnemyMode::Entering => {
+            let target = (*enemy).route.front().cloned().unwrap_or(slot);
+
+            if
+                waves::fly_towards(&mut pos, target, dist) &&
+                (*enemy).route.pop_front().is_none()
+            {
+                (*enemy).mode = EnemyMode::InFormation;
+            }
+        }
+        EnemyMode::Diving => match (*enemy).route.front().cloned() {
+            Some(target) => {
+                if waves::fly_towards(&mut pos, target, dist) {
+                    (*enemy).route.pop_front();
+                }
+            }
+            None => {
+                // gone off the bottom, comes back in from the top
+                pos = (slot.0, -ENEMY_RETURN_HEIGHT);
+                (*enemy).prev_xf = pos.0;
+                (*enemy).prev_yf = pos.1;
+                (*enemy).mode = EnemyMode::Entering;
+            }
+        },
+        EnemyMode::Capturing => match (*enemy).route.front().cloned() {
+            Some(target) => {
+                if waves::fly_towards(&mut pos, target, dist) {
+                    (*enemy).route.pop_front();
+                }
+            }
+            None => {
+                (*enemy).mode = EnemyMode::Beaming(BEAM_TIME);
+            }
+        },
+        EnemyMode::Beaming(_) => {}
     }
+
+    (*enemy).xf = pos.0;
+    (*enemy).yf = pos.1;
 }
 
-fn render(
+fn render<B: Backend>(
     state: &mut GameState,
-    renderer: &mut Renderer
+    renderer: &mut Renderer<B>,
+    alpha: f32
 ) ->
     Result<(), Box<dyn Error>>
 {
+    if (*state).redraw {
+        renderer.redraw();
+        (*state).redraw = false;
+    }
+
+    let (width, height) = renderer.size()?;
+
+    // the game goes on, there's just nothing to see
+    // until the terminal is big enough again
+    if
+        width < (*state).size.0 + PANEL_WIDTH ||
+        height < (*state).size.1
+    {
+        return render_too_small(state, renderer, (width, height));
+    }
+
     let mut wall_view_data = Vec::<Vec<char>>::new();
 
-    for _i in 0..S_SIZE.1 {
+    for _i in 0..(*state).size.1 {
         wall_view_data.push(vec!['|', '|']);
     }
 
     let wall_view =
-        Representation::new(' ', wall_view_data);
+        Representation::new(' ', wall_view_data)
+            .with_style(Style::fg(Color::DarkBlue));
+
+    let lable_style = Style {
+        bold: true,
+        ..Style::fg(Color::Cyan)
+    };
 
     let bullets_lable_view =
         Representation::new(' ',
-            vec![vec!['A', 'M', 'M', 'O', ':']]);
+            vec![vec!['A', 'M', 'M', 'O', ':']])
+            .with_style(lable_style);
     let bullets_lable_location =
         Location {
-            x: (S_SIZE.0 + 3) as i32,
-            y: (S_SIZE.1 - 3) as i32
+            x: ((*state).size.0 + 3) as i32,
+            y: ((*state).size.1 - 3) as i32
         };
 
     let score_lable_view =
         Representation::new(' ',
-            vec![vec!['S', 'C', 'O', 'R', 'E', ':']]);
+            vec![vec!['S', 'C', 'O', 'R', 'E', ':']])
+            .with_style(lable_style);
     let score_lable_location =
         Location {
-            x: (S_SIZE.0 + 3) as i32,
-            y: 2 as i32
+            x: ((*state).size.0 + 3) as i32,
+            y: 2
         };
 
     let score_view =
         Representation::new(' ',
-            vec![(*state).score.to_string().chars().collect()]);
+            vec![(*state).score.to_string().chars().collect()])
+            .with_style(Style::fg(Color::White));
     let score_location =
         Location {
-            x: (S_SIZE.0 + 3) as i32,
-            y: 3 as i32
+            x: ((*state).size.0 + 3) as i32,
+            y: 3
+        };
+
+    let stage_lable_view =
+        Representation::new(' ',
+            vec![vec!['S', 'T', 'A', 'G', 'E', ':']])
+            .with_style(lable_style);
+    let stage_lable_location =
+        Location {
+            x: ((*state).size.0 + 3) as i32,
+            y: 5
+        };
+
+    let stage_number = (*state).wave.number(&(*state).stages);
+
+    let stage_view =
+        Representation::new(' ',
+            vec![stage_number.to_string().chars().collect()])
+            .with_style(Style::fg(Color::White));
+    let stage_location =
+        Location {
+            x: ((*state).size.0 + 3) as i32,
+            y: 6
+        };
+
+    let lives_lable_view =
+        Representation::new(' ',
+            vec![vec!['L', 'I', 'V', 'E', 'S', ':']])
+            .with_style(lable_style);
+    let lives_lable_location =
+        Location {
+            x: ((*state).size.0 + 3) as i32,
+            y: 8
+        };
+
+    let lives_view =
+        Representation::new(' ',
+            vec![(*state).lives.to_string().chars().collect()])
+            .with_style(Style::fg(Color::White));
+    let lives_location =
+        Location {
+            x: ((*state).size.0 + 3) as i32,
+            y: 9
         };
 
+    let mut stars_locations = Vec::<(Location, usize)>::new();
+
+    for layer in (*state).starfield.layers().iter() {
+        for flake in layer.flakes.iter() {
+            stars_locations.push((
+                Location {
+                    x: flake.xf as i32,
+                    y: lerp(flake.prev_yf, flake.yf, alpha),
+                },
+                layer.view,
+            ));
+        }
+    }
+
     let mut render_queue =
-        Vec::<(&Location, &Representation)>::new();
+        Vec::<(Layer, &Location, &Representation)>::new();
+
+    // the background goes under everything else
+    for i in 0..stars_locations.len() {
+        render_queue.push((
+            Layer::Background,
+            &stars_locations[i].0,
+            &(*state).views[stars_locations[i].1]));
+    }
 
     let wall_location =
         Location {
-            x: (S_SIZE.0 - 3) as i32,
+            x: ((*state).size.0 - 3) as i32,
             y: 0,
         };
 
@@ -425,42 +1901,106 @@ fn render(
             y: (*state).turret.y,
         };
 
-    render_queue.push((
-        &turret_location,
-        &(*state).views[(*state).turret.view]));
+    let turret_view = (*state).turret.animation.frame(
+        &(*state).views[(*state).turret.view]);
+
+    // shown until it's done exploding, the last one included,
+    // then gone while respawning and blinking while invulnerable
+    let turret_exploding =
+        !(*state).turret.animation.is_finished(
+            &(*state).views[(*state).turret.view]);
+    let turret_visible =
+        turret_exploding ||
+        (
+            (*state).lives > 0 &&
+            (*state).respawn <= 0.0 &&
+            (
+                (*state).invulnerable <= 0.0 ||
+                ((*state).invulnerable * 8.0).ceil() as i32 % 2 == 0
+            )
+        );
+
+    let second_turret_location =
+        Location {
+            x: (*state).turret.x +
+                (*state).views[(*state).turret.view].data()[0].len() as i32,
+            y: (*state).turret.y,
+        };
+
+    if turret_visible {
+        render_queue.push((
+            Layer::Gameplay,
+            &turret_location,
+            turret_view));
+
+        if (*state).dual {
+            render_queue.push((
+                Layer::Gameplay,
+                &second_turret_location,
+                turret_view));
+        }
+    }
 
     render_queue.push((
+        Layer::Hud,
         &wall_location,
         &wall_view));
 
     render_queue.push((
+        Layer::Hud,
         &bullets_lable_location,
         &bullets_lable_view));
 
     render_queue.push((
+        Layer::Hud,
         &score_lable_location,
         &score_lable_view));
 
     render_queue.push((
+        Layer::Hud,
         &score_location,
         &score_view));
 
+    render_queue.push((
+        Layer::Hud,
+        &stage_lable_location,
+        &stage_lable_view));
+
+    render_queue.push((
+        Layer::Hud,
+        &stage_location,
+        &stage_view));
+
+    render_queue.push((
+        Layer::Hud,
+        &lives_lable_location,
+        &lives_lable_view));
+
+    render_queue.push((
+        Layer::Hud,
+        &lives_location,
+        &lives_view));
+
 
 
     let mut bullets_locations = Vec::<Location>::new();
 
     for i in 0..(*state).bullets.len() {
+        let bullet_ptr = &(*state).bullets[i];
+
         bullets_locations.push(Location {
-            x: (*state).bullets[i].x,
-            y: (*state).bullets[i].y,
+            x: (*bullet_ptr).x,
+            y: lerp((*bullet_ptr).prev_yf, (*bullet_ptr).yf, alpha),
         });
     }
 
+    let mut ammo_locations = Vec::<Location>::new();
+
     for i in 0..(*state).ammo {
-        let x = S_SIZE.0 + 9;
-        let y = S_SIZE.1 - 3;
+        let x = (*state).size.0 + 9;
+        let y = (*state).size.1 - 3;
 
-        bullets_locations.push(Location {
+        ammo_locations.push(Location {
             x: x as i32 + i as i32,
             y: y as i32,
         });
@@ -468,87 +2008,488 @@ fn render(
 
     for i in 0..bullets_locations.len() {
         render_queue.push((
+            Layer::Gameplay,
             &bullets_locations[i],
             &(*state).views[(*state).bullet_view]));
     }
 
+    for i in 0..ammo_locations.len() {
+        render_queue.push((
+            Layer::Hud,
+            &ammo_locations[i],
+            &(*state).views[(*state).bullet_view]));
+    }
+
 
 
     let mut enemies_locations = Vec::<Location>::new();
 
     for i in 0..(*state).enemies.len() {
+        let enemy_ptr = &(*state).enemies[i];
+
         enemies_locations.push(Location {
-            x: (*state).enemies[i].x,
-            y: (*state).enemies[i].y,
+            x: lerp((*enemy_ptr).prev_xf, (*enemy_ptr).xf, alpha),
+            y: lerp((*enemy_ptr).prev_yf, (*enemy_ptr).yf, alpha),
         });
     }
 
     for i in 0..enemies_locations.len() {
+        let enemy_ptr = &(*state).enemies[i];
+
         render_queue.push((
+            Layer::Gameplay,
             &enemies_locations[i],
-            &(*state).views[(*state).enemy_view]));
+            (*enemy_ptr).animation.frame(
+                &(*state).views[(*enemy_ptr).view])));
+    }
+
+    // captured turrets ride on top of their bosses,
+    // beams hang below them
+    let mut captives_locations = Vec::<Location>::new();
+    let mut beams_locations = Vec::<Location>::new();
+
+    let beam_width =
+        (*state).views[(*state).beam_view].data()[0].len() as i32;
+
+    for i in 0..(*state).enemies.len() {
+        let enemy_ptr = &(*state).enemies[i];
+        let enemy_view = &(*state).views[(*enemy_ptr).view];
+        let location = &enemies_locations[i];
+
+        if (*enemy_ptr).captive {
+            captives_locations.push(Location {
+                x: (*location).x + 1,
+                y: (*location).y - 2,
+            });
+        }
+
+        if let EnemyMode::Beaming(_) = (*enemy_ptr).mode {
+            beams_locations.push(Location {
+                x: (*location).x +
+                    enemy_view.data()[0].len() as i32 / 2 - beam_width / 2,
+                y: (*location).y + enemy_view.data().len() as i32,
+            });
+        }
+    }
+
+    for i in 0..captives_locations.len() {
+        render_queue.push((
+            Layer::Gameplay,
+            &captives_locations[i],
+            &(*state).views[(*state).captured_view]));
+    }
+
+    for i in 0..beams_locations.len() {
+        render_queue.push((
+            Layer::Gameplay,
+            &beams_locations[i],
+            &(*state).views[(*state).beam_view]));
+    }
+
+    let freed_location = match &(*state).freed {
+        Some(freed) => Location {
+            x: lerp((*freed).prev_xf, (*freed).xf, alpha),
+            y: lerp((*freed).prev_yf, (*freed).yf, alpha),
+        },
+        None => Location { x: 0, y: 0 },
+    };
+    if (*state).freed.is_some() {
+        render_queue.push((
+            Layer::Gameplay,
+            &freed_location,
+            &(*state).views[(*state).turret.view]));
+    }
+
+    let mut shots_locations = Vec::<Location>::new();
+
+    for i in 0..(*state).shots.len() {
+        let shot_ptr = &(*state).shots[i];
+
+        shots_locations.push(Location {
+            x: lerp((*shot_ptr).prev_xf, (*shot_ptr).xf, alpha),
+            y: lerp((*shot_ptr).prev_yf, (*shot_ptr).yf, alpha),
+        });
+    }
+
+    for i in 0..shots_locations.len() {
+        render_queue.push((
+            Layer::Gameplay,
+            &shots_locations[i],
+            &(*state).views[(*state).shot_view]));
+    }
+
+    // effects go over everything in the game
+    let mut effects_locations = Vec::<Location>::new();
+
+    for i in 0..(*state).effects.len() {
+        let effect_ptr = &(*state).effects[i];
+
+        effects_locations.push(Location {
+            x: lerp((*effect_ptr).prev_xf, (*effect_ptr).xf, alpha),
+            y: lerp((*effect_ptr).prev_yf, (*effect_ptr).yf, alpha),
+        });
+    }
+
+    for i in 0..effects_locations.len() {
+        let effect_ptr = &(*state).effects[i];
+
+        render_queue.push((
+            Layer::Effects,
+            &effects_locations[i],
+            (*effect_ptr).animation.frame(
+                effect_view(&(*state).views, &(*effect_ptr).view))));
+    }
+
+    // the quit dialog is drawn over the screen it was opened from
+    let (screen, confirm_quit) = match &(*state).screen {
+        Screen::ConfirmQuit(prev) => (&**prev, true),
+        screen => (screen, false),
+    };
+
+    let stage_title_view = text_view(
+        &[format!("STAGE {}", stage_number)],
+        Style { bold: true, ..Style::fg(Color::Cyan) });
+    let stage_title_location =
+        centered(
+            (*state).size, &stage_title_view, (*state).size.1 as i32 / 2);
+    if
+        (*state).wave.time < 0.0 &&
+        matches!(screen, Screen::Playing | Screen::Paused)
+    {
+        render_queue.push((
+            Layer::Overlay,
+            &stage_title_location,
+            &stage_title_view));
     }
 
     let gameover_location =
         Location {
-            x: (S_SIZE.0 + 16) as i32 / 2 - 31,
-            y: S_SIZE.1 as i32 / 2 - 3,
+            x: ((*state).size.0 + PANEL_WIDTH) as i32 / 2 - 31,
+            y: (*state).size.1 as i32 / 2 - 3,
         };
-    if (*state).gameover {
+    if let Screen::GameOver = screen {
         render_queue.push((
+            Layer::Overlay,
             &gameover_location,
             &(*state).views[(*state).gameover_view]));
     }
 
+    let name_entry_view = match screen {
+        Screen::NameEntry(name) => text_view(&[
+            "+--------------------------+".to_string(),
+            "|      NEW HIGH SCORE!     |".to_string(),
+            "|                          |".to_string(),
+            format!("|   NAME: {:_<8}         |", name),
+            "|                          |".to_string(),
+            "|   Enter - save           |".to_string(),
+            "+--------------------------+".to_string(),
+        ], Style { bold: true, ..Style::fg(Color::Yellow) }),
+        _ => text_view(&[], Style::default()),
+    };
+    let name_entry_location =
+        centered(
+            (*state).size, &name_entry_view, (*state).size.1 as i32 / 2 - 3);
+    if let Screen::NameEntry(_) = screen {
+        render_queue.push((
+            Layer::Overlay,
+            &name_entry_location,
+            &name_entry_view));
+    }
+
+    let highscores_view = highscores_view(&(*state).highscores);
+    let highscores_location =
+        centered((*state).size, &highscores_view, 2);
+    if let Screen::Title | Screen::GameOver | Screen::NameEntry(_) = screen {
+        render_queue.push((
+            Layer::Overlay,
+            &highscores_location,
+            &highscores_view));
+    }
+
     let prestart_location =
-        Location {
-            x: (S_SIZE.0 + 16) as i32 / 2 - 13,
-            y: S_SIZE.1 as i32 / 2 - 3,
-        };
-    if !(*state).started {
+        centered(
+            (*state).size,
+            &(*state).views[(*state).prestart_view],
+            (*state).size.1 as i32 / 2 - 3);
+    if let Screen::Title = screen {
         render_queue.push((
+            Layer::Overlay,
             &prestart_location,
             &(*state).views[(*state).prestart_view]));
     }
 
+    let settings_view = match screen {
+        Screen::Settings(menu) => settings_view(&(*state).bindings, menu),
+        _ => text_view(&[], Style::default()),
+    };
+    let settings_location =
+        centered((*state).size, &settings_view, 4);
+    if let Screen::Settings(_) = screen {
+        render_queue.push((
+            Layer::Overlay,
+            &settings_location,
+            &settings_view));
+    }
+
+    let paused_location =
+        centered(
+            (*state).size,
+            &(*state).views[(*state).paused_view],
+            (*state).size.1 as i32 / 2 - 3);
+    if let Screen::Paused = screen {
+        render_queue.push((
+            Layer::Overlay,
+            &paused_location,
+            &(*state).views[(*state).paused_view]));
+    }
+
+    let confirm_quit_location =
+        centered(
+            (*state).size,
+            &(*state).views[(*state).confirm_quit_view],
+            (*state).size.1 as i32 / 2 - 3);
+    if confirm_quit {
+        render_queue.push((
+            Layer::Overlay,
+            &confirm_quit_location,
+            &(*state).views[(*state).confirm_quit_view]));
+    }
 
     renderer.render(&render_queue)?;
 
     Ok(())
 }
 
-fn load_view(
-    filepath: &str, views: &mut Vec<Representation>
+fn render_too_small<B: Backend>(
+    state: &mut GameState,
+    renderer: &mut Renderer<B>,
+    terminal: (u16, u16)
 ) ->
-    Result<usize, Box<dyn Error>>
+    Result<(), Box<dyn Error>>
 {
-    views.push(Representation::from_file(filepath)?);
+    let view = text_view(&[
+        "Terminal too small".to_string(),
+        format!("{}x{}, needs {}x{}",
+            terminal.0, terminal.1,
+            (*state).size.0 + PANEL_WIDTH, (*state).size.1),
+    ], Style { bold: true, ..Style::fg(Color::Red) });
+
+    let location = Location {
+        x: terminal.0 as i32 / 2 - view.data()[0].len() as i32 / 2,
+        y: terminal.1 as i32 / 2 - 1,
+    };
 
-    Ok(views.len() - 1)
+    renderer.render(&[(Layer::Overlay, &location, &view)])?;
+
+    Ok(())
 }
 
-fn create_enemies() -> Vec<Enemy> {
-    let mut enemies = Vec::<Enemy>::new();
+// location that puts `view` in the middle of a playfield `size` wide
+fn centered(size: (u16, u16), view: &Representation, y: i32) -> Location {
+    let width = view.data()[0].len() as i32;
 
-    for i in 0..3 {
-        enemies.push(create_enemy(10, 5 * i));
+    Location {
+        x: (size.0 as i32 - 3) / 2 - width / 2,
+        y: y,
+    }
+}
+
+// block of text with every line padded to the same width,
+// spaces are drawn too
+fn text_view(lines: &[String], style: Style) -> Representation {
+    let width = lines.iter()
+        .map(|line| text_width(line))
+        .max()
+        .unwrap_or(0);
+
+    let mut data = Vec::<Vec<char>>::new();
+
+    for line in lines {
+        data.push(pad(line, width).chars().collect());
+    }
+
+    if data.is_empty() {
+        data.push(Vec::new());
     }
 
-    enemies
+    Representation::new('\0', data)
+        .with_style(style)
 }
 
-fn create_enemy(spd: i32, y: i32) -> Enemy {
-    let mut rng = rand::thread_rng();
-    let x = rng.gen_range(0, (S_SIZE.0 as i32) - 9);
+// `line` followed by spaces up to `width` columns,
+// unlike `{:<width$}` it counts columns rather than chars
+fn pad(line: &str, width: usize) -> String {
+    let columns = text_width(line);
 
-    Enemy {
-        xf: x as f32,
-        yf: y as f32,
-        x: x,
-        y: y,
-        target_x: x,
-        target_y: y,
-        speed: spd,
-        speedup: 0
+    format!("{}{}", line, " ".repeat(width.saturating_sub(columns)))
+}
+
+// controls box shown before the game starts,
+// lists whatever keys are bound at the moment
+fn prestart_view(bindings: &Bindings) -> Representation {
+    let controls = [
+        (Action::MoveLeft, "move left"),
+        (Action::MoveRight, "move right"),
+        (Action::Fire, "shoot"),
+        (Action::Pause, "pause"),
+        (Action::Settings, "settings"),
+        (Action::Quit, "exit"),
+    ];
+
+    let mut rows = Vec::<(String, &str)>::new();
+
+    for (action, description) in controls.iter() {
+        rows.push((keys_label(bindings, *action), description));
+    }
+
+    let start = format!(
+        "Press {} to start", keys_label(bindings, Action::Start));
+
+    let keys_width = rows.iter()
+        .map(|(keys, _)| text_width(keys))
+        .max()
+        .unwrap_or(0);
+
+    let mut body = Vec::<String>::new();
+
+    body.push(String::new());
+    for (keys, description) in rows.iter() {
+        body.push(format!(
+            "{} - {}", pad(keys, keys_width), description));
+    }
+    body.push(String::new());
+    body.push(start);
+
+    let inner_width = body.iter()
+        .map(|line| text_width(line))
+        .max()
+        .unwrap_or(0) + 2;
+
+    let mut lines = Vec::<String>::new();
+
+    lines.push(format!(" {} ", "_".repeat(inner_width)));
+    for line in body.iter() {
+        lines.push(format!("| {} |", pad(line, inner_width - 2)));
+    }
+    lines.push(format!("|{}|", "_".repeat(inner_width)));
+
+    text_view(&lines, Style::fg(Color::Cyan))
+}
+
+fn settings_view(bindings: &Bindings, menu: &SettingsMenu) -> Representation {
+    let mut lines = vec![
+        "SETTINGS".to_string(),
+        String::new(),
+        "Controls".to_string(),
+        format!("({})", BINDINGS_FILE.trim_start_matches("./")),
+        String::new(),
+    ];
+
+    for (i, action) in Action::all().iter().enumerate() {
+        let selected = i == menu.selected;
+
+        let keys =
+            if selected && menu.capturing {
+                "press a key...".to_string()
+            } else {
+                keys_label(bindings, *action)
+            };
+
+        lines.push(format!(
+            "{} {:<11} {}",
+            if selected { ">" } else { " " }, action.name(), keys));
     }
+
+    lines.push(String::new());
+    if let Some(error) = &menu.error {
+        lines.push(error.clone());
+        lines.push(String::new());
+    }
+    lines.push("↑/↓ - select, Enter - change key".to_string());
+    lines.push("Esc - back".to_string());
+
+    framed_view(&lines, Style::fg(Color::Cyan))
+}
+
+fn paused_view(bindings: &Bindings) -> Representation {
+    framed_view(&[
+        "PAUSED".to_string(),
+        String::new(),
+        format!("{} - resume", keys_label(bindings, Action::Pause)),
+        format!("{} - quit", keys_label(bindings, Action::Quit)),
+    ], Style { bold: true, ..Style::fg(Color::White) })
+}
+
+fn confirm_quit_view(bindings: &Bindings) -> Representation {
+    framed_view(&[
+        "Quit the game?".to_string(),
+        String::new(),
+        format!("{} / Y - yes", keys_label(bindings, Action::Start)),
+        format!("{} / N - no", keys_label(bindings, Action::Quit)),
+    ], Style { bold: true, ..Style::fg(Color::Red) })
+}
+
+// all keys of an action, as shown to the player
+fn keys_label(bindings: &Bindings, action: Action) -> String {
+    let keys: Vec<String> = bindings.keys(action).iter()
+        .map(bindings::key_label)
+        .collect();
+
+    keys.join(" / ")
+}
+
+// text in a box
+fn framed_view(lines: &[String], style: Style) -> Representation {
+    let width = lines.iter()
+        .map(|line| text_width(line))
+        .max()
+        .unwrap_or(0);
+
+    let mut framed = Vec::<String>::new();
+
+    framed.push(format!("+{}+", "-".repeat(width + 2)));
+    for line in lines {
+        framed.push(format!("| {} |", pad(line, width)));
+    }
+    framed.push(format!("+{}+", "-".repeat(width + 2)));
+
+    text_view(&framed, style)
+}
+
+fn highscores_view(highscores: &HighScores) -> Representation {
+    let mut lines = vec![
+        "         HIGH SCORES".to_string(),
+        String::new(),
+    ];
+
+    for (i, entry) in highscores.entries().iter().enumerate() {
+        lines.push(format!(
+            "{}. {:<8} {:>7} {}",
+            i + 1, entry.name, entry.score, entry.date));
+    }
+
+    if highscores.entries().is_empty() {
+        lines.push("        no scores yet".to_string());
+    }
+
+    text_view(&lines, Style::fg(Color::Yellow))
+}
+
+// position between the previous and the current update
+fn lerp(prev: f32, curr: f32, alpha: f32) -> i32 {
+    (prev + (curr - prev) * alpha).round() as i32
+}
+
+fn load_view(
+    filepath: &str, views: &mut Vec<Representation>
+) ->
+    Result<usize, AssetError>
+{
+    views.push(Representation::from_file(filepath)?);
+
+    Ok(views.len() - 1)
+}
+
+fn frame_duration() -> Duration {
+    Duration::from_millis(1000 / FPS as u64)
 }
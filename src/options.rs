@@ -0,0 +1,132 @@
+use std::error::Error;
+
+// what the binary has been started to do
+#[derive(PartialEq, Debug)]
+pub enum Mode {
+    Play,
+    // load all the assets, report what's wrong with them and quit
+    CheckAssets,
+    // show the given object file and its animations
+    View(String),
+}
+
+pub struct Options {
+    pub mode: Mode,
+    // run without a terminal for the given number
+    // of frames and print the last frame to stdout
+    pub headless: Option<u64>,
+    // update the game in constant steps of one frame
+    // regardless of how long frames actually take
+    pub fixed_step: bool,
+    // seed for the game's random number generator,
+    // picked at random when not given
+    pub seed: Option<u64>,
+    // file to write every input event to
+    pub record: Option<String>,
+    // file to read input events from instead of the keyboard
+    pub replay: Option<String>,
+    // playfield size to use instead of the configured one
+    pub size: Option<(u16, u16)>,
+    // reload object files while playing whenever they change
+    pub dev: bool,
+}
+
+impl Options {
+    pub fn parse(
+        args: &[String]
+    ) ->
+        Result<Options, Box<dyn Error>>
+    {
+        let mut options = Options {
+            mode: Mode::Play,
+            headless: None,
+            fixed_step: false,
+            seed: None,
+            record: None,
+            replay: None,
+            size: None,
+            dev: false,
+        };
+
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_ref() {
+                "--headless" => {
+                    options.headless =
+                        Some(value(args, i)?.parse()?);
+                    i += 1;
+                }
+                "--seed" => {
+                    options.seed =
+                        Some(value(args, i)?.parse()?);
+                    i += 1;
+                }
+                "--record" => {
+                    options.record =
+                        Some(value(args, i)?.to_string());
+                    i += 1;
+                }
+                "--replay" => {
+                    options.replay =
+                        Some(value(args, i)?.to_string());
+                    i += 1;
+                }
+                "--size" => {
+                    options.size =
+                        Some(size(value(args, i)?)?);
+                    i += 1;
+                }
+                "--fixed-step" => {
+                    options.fixed_step = true;
+                }
+                "--dev" => {
+                    options.dev = true;
+                }
+                "check-assets" if i == 0 => {
+                    options.mode = Mode::CheckAssets;
+                }
+                "view" if i == 0 => {
+                    options.mode =
+                        Mode::View(value(args, i)?.to_string());
+                    i += 1;
+                }
+                arg => {
+                    return Err(format!(
+                        "unknown argument {:?}", arg).into());
+                }
+            }
+
+            i += 1;
+        }
+
+        Ok(options)
+    }
+}
+
+fn value(
+    args: &[String], i: usize
+) ->
+    Result<&str, Box<dyn Error>>
+{
+    match args.get(i + 1) {
+        Some(value) => Ok(value),
+        None => Err(format!(
+            "{} expects a value", args[i]).into()),
+    }
+}
+
+// "WIDTHxHEIGHT", e.g. "60x40"
+fn size(
+    value: &str
+) ->
+    Result<(u16, u16), Box<dyn Error>>
+{
+    let mut parts = value.splitn(2, 'x');
+
+    match (parts.next(), parts.next()) {
+        (Some(width), Some(height)) =>
+            Ok((width.parse()?, height.parse()?)),
+        _ => Err(format!(
+            "--size expects WIDTHxHEIGHT, not {:?}", value).into()),
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+
+use crossterm::input::InputEvent;
+use crossterm::input::KeyEvent;
+
+// Recordings are plain text: a `seed <n>` header followed
+// by one `<frame> <event>` line per keyboard event, e.g.
+//
+//   seed 42
+//   0 Enter
+//   17 Left
+//   20 Char:32
+
+pub struct Recorder {
+    file: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(
+        filepath: &str, seed: u64
+    ) ->
+        Result<Recorder, Box<dyn Error>>
+    {
+        let mut file = BufWriter::new(File::create(filepath)?);
+
+        writeln!(file, "seed {}", seed)?;
+
+        Ok(Recorder {
+            file: file,
+        })
+    }
+
+    pub fn record(
+        &mut self, frame: u64, events: &[InputEvent]
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        for event in events {
+            if let InputEvent::Keyboard(key) = event {
+                if let Some(encoded) = encode_key(key) {
+                    writeln!((*self).file, "{} {}", frame, encoded)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(e) = (*self).file.flush() {
+            log::error!("failed to write recording: {}", e);
+        }
+    }
+}
+
+pub struct Replay {
+    seed: Option<u64>,
+    events: VecDeque<(u64, InputEvent)>,
+}
+
+impl Replay {
+    pub fn load(
+        filepath: &str
+    ) ->
+        Result<Replay, Box<dyn Error>>
+    {
+        let contents = fs::read_to_string(filepath)?;
+
+        let mut replay = Replay {
+            seed: None,
+            events: VecDeque::new(),
+        };
+
+        for (n, line) in contents.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, ' ');
+            let first = parts.next().unwrap_or("");
+            let rest = parts.next().unwrap_or("");
+
+            if first == "seed" {
+                replay.seed = Some(rest.parse()?);
+                continue;
+            }
+
+            let frame: u64 = first.parse()
+                .map_err(|_| format!(
+                    "{}:{}: bad frame number {:?}",
+                    filepath, n + 1, first))?;
+
+            let key = decode_key(rest)
+                .ok_or_else(|| format!(
+                    "{}:{}: unknown event {:?}",
+                    filepath, n + 1, rest))?;
+
+            replay.events.push_back(
+                (frame, InputEvent::Keyboard(key)));
+        }
+
+        Ok(replay)
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        (*self).seed
+    }
+
+    // forget everything recorded from frame `frames` on
+    pub fn truncate(&mut self, frames: u64) {
+        (*self).events.retain(|(frame, _)| *frame < frames);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        (*self).events.is_empty()
+    }
+
+    pub fn events(&mut self, frame: u64) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        while let Some((at, _)) = (*self).events.front() {
+            if *at > frame {
+                break;
+            }
+
+            if let Some((_, event)) = (*self).events.pop_front() {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+}
+
+fn encode_key(key: &KeyEvent) -> Option<String> {
+    let encoded = match key {
+        KeyEvent::Backspace => "Backspace".to_string(),
+        KeyEvent::Enter => "Enter".to_string(),
+        KeyEvent::Left => "Left".to_string(),
+        KeyEvent::Right => "Right".to_string(),
+        KeyEvent::Up => "Up".to_string(),
+        KeyEvent::Down => "Down".to_string(),
+        KeyEvent::Home => "Home".to_string(),
+        KeyEvent::End => "End".to_string(),
+        KeyEvent::PageUp => "PageUp".to_string(),
+        KeyEvent::PageDown => "PageDown".to_string(),
+        KeyEvent::Tab => "Tab".to_string(),
+        KeyEvent::BackTab => "BackTab".to_string(),
+        KeyEvent::Delete => "Delete".to_string(),
+        KeyEvent::Insert => "Insert".to_string(),
+        KeyEvent::Esc => "Esc".to_string(),
+        KeyEvent::F(n) => format!("F:{}", n),
+        // chars are written as code points,
+        // so spaces don't get lost
+        KeyEvent::Char(c) => format!("Char:{}", *c as u32),
+        KeyEvent::Alt(c) => format!("Alt:{}", *c as u32),
+        KeyEvent::Ctrl(c) => format!("Ctrl:{}", *c as u32),
+        _ => return None,
+    };
+
+    Some(encoded)
+}
+
+fn decode_key(encoded: &str) -> Option<KeyEvent> {
+    let mut parts = encoded.splitn(2, ':');
+    let name = parts.next()?;
+    let arg = parts.next();
+
+    let code_point = || {
+        arg.and_then(|a| a.parse::<u32>().ok())
+            .and_then(std::char::from_u32)
+    };
+
+    let key = match name {
+        "Backspace" => KeyEvent::Backspace,
+        "Enter" => KeyEvent::Enter,
+        "Left" => KeyEvent::Left,
+        "Right" => KeyEvent::Right,
+        "Up" => KeyEvent::Up,
+        "Down" => KeyEvent::Down,
+        "Home" => KeyEvent::Home,
+        "End" => KeyEvent::End,
+        "PageUp" => KeyEvent::PageUp,
+        "PageDown" => KeyEvent::PageDown,
+        "Tab" => KeyEvent::Tab,
+        "BackTab" => KeyEvent::BackTab,
+        "Delete" => KeyEvent::Delete,
+        "Insert" => KeyEvent::Insert,
+        "Esc" => KeyEvent::Esc,
+        "F" => KeyEvent::F(arg?.parse().ok()?),
+        "Char" => KeyEvent::Char(code_point()?),
+        "Alt" => KeyEvent::Alt(code_point()?),
+        "Ctrl" => KeyEvent::Ctrl(code_point()?),
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crossterm::input::InputEvent;
+    use crossterm::input::KeyEvent;
+
+    use crate::recording::Replay;
+    use crate::recording::decode_key;
+    use crate::recording::encode_key;
+
+    #[test]
+    fn keys_come_back_as_they_were_written() {
+        let keys = [
+            KeyEvent::Enter,
+            KeyEvent::Left,
+            KeyEvent::BackTab,
+            KeyEvent::F(12),
+            KeyEvent::Char(' '),
+            KeyEvent::Char(':'),
+            KeyEvent::Char('世'),
+            KeyEvent::Alt('x'),
+            KeyEvent::Ctrl('c'),
+        ];
+
+        for key in keys.iter() {
+            let encoded = encode_key(key).unwrap();
+
+            assert_eq!(decode_key(&encoded), Some(*key), "{}", encoded);
+        }
+
+        assert_eq!(decode_key("Char"), None);
+        assert_eq!(decode_key("Char:x"), None);
+        assert_eq!(decode_key("Jump"), None);
+    }
+
+    #[test]
+    fn events_come_on_their_frame_until_truncated() {
+        let key = |key| InputEvent::Keyboard(key);
+
+        let mut replay = Replay {
+            seed: None,
+            events: VecDeque::from(vec![
+                (0, key(KeyEvent::Enter)),
+                (2, key(KeyEvent::Left)),
+                (2, key(KeyEvent::Right)),
+                (5, key(KeyEvent::Esc)),
+            ]),
+        };
+
+        replay.truncate(5);
+
+        assert_eq!(replay.events(0), vec![key(KeyEvent::Enter)]);
+        assert!(replay.events(1).is_empty());
+        assert_eq!(
+            replay.events(2),
+            vec![key(KeyEvent::Left), key(KeyEvent::Right)]);
+        assert!(replay.is_finished());
+    }
+}
@@ -1,30 +1,92 @@
 pub mod types;
 
 use std::error::Error;
+use std::io::Write;
 use std::io::stdout;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
 use crossterm::ExecutableCommand;
+use crossterm::QueueableCommand;
 use crossterm::input::input;
+use crossterm::screen::EnterAlternateScreen;
+use crossterm::screen::LeaveAlternateScreen;
 use crossterm::screen::RawScreen;
-use crossterm::terminal;
+use crossterm::style::Attribute;
+use crossterm::style::SetAttribute;
 use crossterm::cursor;
 
-pub fn init(
+// set while the game has the terminal to itself
+static IN_USE: AtomicBool = AtomicBool::new(false);
+
+// the frame always takes the whole terminal,
+// whatever size it is or gets resized to
+pub fn init() -> Result<types::Renderer, Box<dyn Error>> {
+    // raw mode keeps Ctrl-C from sending SIGINT, but a signal
+    // can still come from elsewhere; instead of getting killed
+    // with the terminal as it is the game quits like on Ctrl-C
+    let terminated = Arc::new(AtomicBool::new(false));
+
+    // before the terminal is touched, so there's nothing
+    // to undo when this fails
+    signal_hook::flag::register(
+        signal_hook::SIGINT, Arc::clone(&terminated))?;
+    signal_hook::flag::register(
+        signal_hook::SIGTERM, Arc::clone(&terminated))?;
+
+    let raw = RawScreen::into_raw_mode()?;
+    let reader = input().read_async();
+
+    let mut stdout = stdout();
+
+    IN_USE.store(true, Ordering::SeqCst);
+
+    // half set up is given back right away, there's
+    // no backend yet to do it when it's dropped
+    let entered = stdout.execute(EnterAlternateScreen)
+        .and_then(|stdout| stdout.execute(cursor::Hide))
+        .map(|_| ());
+
+    if let Err(e) = entered {
+        restore();
+        return Err(e.into());
+    }
+
+    let backend =
+        types::TerminalBackend::new(raw, reader, stdout, terminated);
+
+    Ok(types::Renderer::new(backend))
+}
+
+// renderer that never touches the terminal,
+// frames end up in memory and input is scripted
+pub fn headless(
     win_width: u16, win_height: u16
 ) ->
-    Result<types::Renderer, Box<dyn Error>>
+    types::Renderer<types::MemoryBackend>
 {
-    let raw = RawScreen::into_raw_mode()?;
-    let reader = input().read_async();
+    let backend =
+        types::MemoryBackend::new(win_width, win_height);
+
+    types::Renderer::new(backend)
+}
+
+// gives the terminal back the way it was before `init`, safe to call
+// any number of times and from the panic hook, only the first call
+// after `init` does anything
+pub fn restore() {
+    if !IN_USE.swap(false, Ordering::SeqCst) {
+        return;
+    }
 
+    // nothing to do about errors, the game is on its way out
     let mut stdout = stdout();
 
-    stdout.execute(
-        terminal::SetSize(
-            win_width, win_height))?;
-    stdout.execute(
-        cursor::Hide)?;
+    let _ = stdout.queue(SetAttribute(Attribute::Reset));
+    let _ = stdout.queue(cursor::Show);
+    let _ = stdout.queue(LeaveAlternateScreen);
+    let _ = stdout.flush();
 
-    Ok(types::Renderer::new(
-        raw, reader, win_width, win_height, stdout))
+    let _ = RawScreen::disable_raw_mode();
 }
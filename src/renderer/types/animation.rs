@@ -0,0 +1,126 @@
+use crate::renderer::types::object::Representation;
+
+pub struct Animation {
+    frames: Vec<Representation>,
+    // seconds every frame is shown for
+    durations: Vec<f32>,
+}
+
+impl Animation {
+    pub fn new(
+        frames: Vec<Representation>,
+        durations: Vec<f32>,
+    ) ->
+        Animation
+    {
+        Animation {
+            frames: frames,
+            durations: durations,
+        }
+    }
+
+    pub fn frames(&self) -> &Vec<Representation> {
+        &(*self).frames
+    }
+
+    pub fn durations(&self) -> &Vec<f32> {
+        &(*self).durations
+    }
+}
+
+// which frame of which of a view's animations an object shows,
+// objects that aren't playing anything show the view as it is
+pub struct AnimationPlayer {
+    animation: Option<String>,
+    frame: usize,
+    // seconds the current frame has been shown for
+    elapsed: f32,
+    looping: bool,
+}
+
+impl AnimationPlayer {
+    pub fn still() -> AnimationPlayer {
+        AnimationPlayer {
+            animation: None,
+            frame: 0,
+            elapsed: 0.0,
+            looping: false,
+        }
+    }
+
+    pub fn playing(animation: &str, looping: bool) -> AnimationPlayer {
+        let mut player = AnimationPlayer::still();
+        player.play(animation, looping);
+
+        player
+    }
+
+    // starts over from the first frame
+    pub fn play(&mut self, animation: &str, looping: bool) {
+        (*self).animation = Some(animation.to_string());
+        (*self).frame = 0;
+        (*self).elapsed = 0.0;
+        (*self).looping = looping;
+    }
+
+    pub fn stop(&mut self) {
+        (*self).animation = None;
+    }
+
+    pub fn update(&mut self, view: &Representation, dt: f32) {
+        let animation = match (*self).animation.as_ref()
+            .and_then(|name| view.animation(name))
+        {
+            Some(animation) => animation,
+            None => return,
+        };
+
+        let count = animation.frames().len();
+
+        (*self).elapsed += dt;
+
+        while
+            (*self).frame < count &&
+            (*self).elapsed >= animation.durations()[(*self).frame]
+        {
+            (*self).elapsed -= animation.durations()[(*self).frame];
+            (*self).frame += 1;
+
+            if (*self).frame == count && (*self).looping {
+                (*self).frame = 0;
+            }
+        }
+    }
+
+    // a one-off animation has shown all of its frames
+    pub fn is_finished(&self, view: &Representation) -> bool {
+        match (*self).animation.as_ref()
+            .and_then(|name| view.animation(name))
+        {
+            Some(animation) =>
+                !(*self).looping && (*self).frame >= animation.frames().len(),
+            None => true,
+        }
+    }
+
+    // frame being shown, counting from 0
+    pub fn index(&self) -> usize {
+        (*self).frame
+    }
+
+    pub fn frame<'a>(&self, view: &'a Representation) -> &'a Representation {
+        let animation = match (*self).animation.as_ref()
+            .and_then(|name| view.animation(name))
+        {
+            Some(animation) => animation,
+            None => return view,
+        };
+
+        // finished animations stay on their last frame
+        let frames = animation.frames();
+        match frames.get((*self).frame).or_else(|| frames.last()) {
+            Some(frame) => frame,
+            None => view,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use yaml_rust::ScanError;
+
+// what can be wrong with an object file, every variant
+// knows the file and, where it can tell, the line
+#[derive(Debug)]
+pub enum AssetError {
+    // the file isn't there or can't be read
+    Missing {
+        path: String,
+        source: io::Error,
+    },
+    // the file isn't YAML, the error has the line in it
+    Yaml {
+        path: String,
+        source: ScanError,
+    },
+    // something every object needs, like `data`
+    MissingField {
+        path: String,
+        field: String,
+    },
+    // `null_char` has to be exactly one character
+    NullChar {
+        path: String,
+        found: String,
+    },
+    // a row of `data` not as wide as the first one
+    RaggedRows {
+        path: String,
+        line: Option<usize>,
+        row: usize,
+        width: usize,
+        expected: usize,
+    },
+    // anything else, like colors that don't exist
+    Invalid {
+        path: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::Missing { path, source } =>
+                write!(f, "{}: can't read the file: {}", path, source),
+            AssetError::Yaml { path, source } =>
+                write!(f, "{}: bad YAML: {}", path, source),
+            AssetError::MissingField { path, field } =>
+                write!(f, "{}: no {}", path, field),
+            AssetError::NullChar { path, found } =>
+                write!(f, "{}: null_char must be a single character, not {:?}",
+                    path, found),
+            AssetError::RaggedRows { path, line, row, width, expected } => {
+                match line {
+                    Some(line) => write!(f, "{}:{}: ", path, line)?,
+                    None => write!(f, "{}: ", path)?,
+                }
+
+                write!(f, "row {} is {} columns wide, the first one {}",
+                    row, width, expected)
+            }
+            AssetError::Invalid { path, message } =>
+                write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssetError::Missing { source, .. } => Some(source),
+            AssetError::Yaml { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
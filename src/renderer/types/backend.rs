@@ -0,0 +1,33 @@
+use std::error::Error;
+
+use crossterm::input::InputEvent;
+
+use crate::renderer::types::Style;
+
+// low level output the renderer draws a frame with,
+// the renderer itself decides what has to be redrawn
+pub trait Backend {
+    fn move_to(
+        &mut self, x: u16, y: u16
+    ) ->
+        Result<(), Box<dyn Error>>;
+
+    fn set_style(
+        &mut self, style: Style
+    ) ->
+        Result<(), Box<dyn Error>>;
+
+    // leaves the cursor right after the text
+    fn print(
+        &mut self, text: &str
+    ) ->
+        Result<(), Box<dyn Error>>;
+
+    // bytes sent to the output since the last flush
+    fn flush(&mut self) -> Result<usize, Box<dyn Error>>;
+
+    // columns and rows there are to draw on
+    fn size(&self) -> Result<(u16, u16), Box<dyn Error>>;
+
+    fn events(&mut self) -> Vec<InputEvent>;
+}
@@ -0,0 +1,86 @@
+use std::str;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// longest grapheme cluster a glyph holds, longer ones
+// (long emoji sequences) are cut short at a char boundary
+const GLYPH_BYTES: usize = 15;
+
+// what a single terminal column shows: one grapheme cluster,
+// or for the column right of a glyph two columns wide a
+// continuation that stands for that glyph's right half
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    bytes: [u8; GLYPH_BYTES],
+    len: u8,
+}
+
+impl Glyph {
+    pub fn new(grapheme: &str) -> Glyph {
+        let mut len = grapheme.len().min(GLYPH_BYTES);
+        while !grapheme.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut bytes = [0; GLYPH_BYTES];
+        bytes[..len].copy_from_slice(&grapheme.as_bytes()[..len]);
+
+        Glyph {
+            bytes: bytes,
+            len: len as u8,
+        }
+    }
+
+    pub fn from_char(ch: char) -> Glyph {
+        Glyph::new(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    pub fn continuation() -> Glyph {
+        Glyph {
+            bytes: [0; GLYPH_BYTES],
+            len: 0,
+        }
+    }
+
+    pub fn is_continuation(&self) -> bool {
+        (*self).len == 0
+    }
+
+    pub fn as_str(&self) -> &str {
+        str::from_utf8(&(*self).bytes[..(*self).len as usize])
+            .unwrap_or("")
+    }
+
+    // columns the glyph takes on the terminal, anything that
+    // isn't a continuation takes at least one
+    pub fn width(&self) -> usize {
+        if (*self).is_continuation() {
+            return 0;
+        }
+
+        (*self).as_str().width().clamp(1, 2)
+    }
+}
+
+// glyphs of `text` column by column,
+// so wide glyphs are followed by a continuation
+pub fn segment(text: &str) -> Vec<Glyph> {
+    let mut glyphs = Vec::<Glyph>::new();
+
+    for grapheme in text.graphemes(true) {
+        let glyph = Glyph::new(grapheme);
+        glyphs.push(glyph);
+
+        if glyph.width() == 2 {
+            glyphs.push(Glyph::continuation());
+        }
+    }
+
+    glyphs
+}
+
+// columns `text` takes on the terminal
+pub fn text_width(text: &str) -> usize {
+    segment(text).len()
+}
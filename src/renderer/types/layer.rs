@@ -0,0 +1,10 @@
+// what gets drawn over what, objects on later
+// layers cover the ones on earlier layers
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Layer {
+    Background,
+    Gameplay,
+    Effects,
+    Hud,
+    Overlay,
+}
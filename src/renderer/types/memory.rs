@@ -0,0 +1,142 @@
+use std::error::Error;
+
+use crossterm::input::InputEvent;
+
+use crate::renderer::types::Backend;
+use crate::renderer::types::Cell;
+use crate::renderer::types::glyph;
+use crate::renderer::types::Style;
+
+// keeps everything drawn in memory instead of a terminal,
+// input comes from events scripted for given frames
+pub struct MemoryBackend {
+    cells: Vec<Vec<Cell>>,
+    cursor: (usize, usize),
+    style: Style,
+    // bytes printed since the last flush
+    written: usize,
+    frame: u64,
+    script: Vec<(u64, InputEvent)>,
+}
+
+impl MemoryBackend {
+    pub fn new(
+        win_width: u16, win_height: u16
+    ) -> MemoryBackend {
+        let width = win_width as usize;
+        let height = win_height as usize;
+
+        MemoryBackend {
+            cells: vec![vec![Cell::blank(); height]; width],
+            cursor: (0, 0),
+            style: Style::default(),
+            written: 0,
+            frame: 0,
+            script: Vec::new(),
+        }
+    }
+
+    // `event` is delivered by the `events` call
+    // made for frame number `frame`, counting from 0
+    pub fn script(&mut self, frame: u64, event: InputEvent) {
+        (*self).script.push((frame, event));
+    }
+
+    // captured frame as text, one line per row
+    pub fn snapshot(&self) -> String {
+        let width = (*self).cells.len();
+        let height = (*self).cells.first().map_or(0, Vec::len);
+
+        let mut snapshot = String::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                snapshot.push_str((*self).cells[x][y].glyph.as_str());
+            }
+
+            snapshot.push('\n');
+        }
+
+        snapshot
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn move_to(
+        &mut self, x: u16, y: u16
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        (*self).cursor = (x as usize, y as usize);
+
+        Ok(())
+    }
+
+    fn set_style(
+        &mut self, style: Style
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        (*self).style = style;
+
+        Ok(())
+    }
+
+    fn print(
+        &mut self, text: &str
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        // like a terminal, a wide glyph takes two cells
+        for glyph in glyph::segment(text) {
+            let (x, y) = (*self).cursor;
+
+            if x < (*self).cells.len() && y < (*self).cells[x].len() {
+                (*self).cells[x][y] = Cell {
+                    glyph: glyph,
+                    style: (*self).style,
+                };
+            }
+
+            (*self).cursor = (x + 1, y);
+        }
+
+        (*self).written += text.len();
+
+        Ok(())
+    }
+
+    // there are no escape codes in memory,
+    // only the text itself counts
+    fn flush(&mut self) -> Result<usize, Box<dyn Error>> {
+        let written = (*self).written;
+        (*self).written = 0;
+
+        Ok(written)
+    }
+
+    fn size(&self) -> Result<(u16, u16), Box<dyn Error>> {
+        let width = (*self).cells.len();
+        let height = (*self).cells.first().map_or(0, Vec::len);
+
+        Ok((width as u16, height as u16))
+    }
+
+    fn events(&mut self) -> Vec<InputEvent> {
+        let frame = (*self).frame;
+        (*self).frame += 1;
+
+        let mut events = Vec::new();
+
+        (*self).script.retain(|(at, event)| {
+            if *at <= frame {
+                events.push(event.clone());
+                false
+            } else {
+                true
+            }
+        });
+
+        events
+    }
+}
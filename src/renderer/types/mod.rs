@@ -1,7 +1,27 @@
+mod animation;
+mod asset_error;
+mod backend;
+mod glyph;
+mod layer;
+mod memory;
 mod object;
 mod renderer;
+mod style;
+mod terminal;
 
 pub type Location = object::Location;
 pub type Representation = object::Representation;
+pub type Style = style::Style;
+pub type Glyph = glyph::Glyph;
+pub type Layer = layer::Layer;
+pub type AnimationPlayer = animation::AnimationPlayer;
+pub type AssetError = asset_error::AssetError;
 
-pub type Renderer = renderer::Renderer;
+pub use backend::Backend;
+pub use glyph::text_width;
+pub type MemoryBackend = memory::MemoryBackend;
+pub type TerminalBackend = terminal::TerminalBackend;
+
+pub type Cell = renderer::Cell;
+pub type Stats = renderer::Stats;
+pub type Renderer<B = TerminalBackend> = renderer::Renderer<B>;
@@ -1,16 +1,35 @@
-use std::error::Error;
+use std::collections::HashMap;
 use std::fs;
 
+use yaml_rust::Event;
+use yaml_rust::Yaml;
 use yaml_rust::YamlLoader;
+use yaml_rust::parser::MarkedEventReceiver;
+use yaml_rust::parser::Parser;
+use yaml_rust::scanner::Marker;
+use yaml_rust::scanner::TScalarStyle;
+
+use crate::renderer::types::animation::Animation;
+use crate::renderer::types::asset_error::AssetError;
+use crate::renderer::types::glyph;
+use crate::renderer::types::glyph::Glyph;
+use crate::renderer::types::style::Style;
+use crate::yaml;
 
 pub struct Location {
     pub x: i32,
     pub y: i32,
 }
 
+// `data` goes column by column, a glyph two columns
+// wide is followed by a continuation in the next one
 pub struct Representation {
-    null_char: char,
-    data: Vec<Vec<char>>,
+    null_char: Glyph,
+    data: Vec<Vec<Glyph>>,
+    styles: Vec<Vec<Style>>,
+    animations: HashMap<String, Animation>,
+    // file it was loaded from
+    source: Option<String>,
 }
 
 impl Representation {
@@ -20,49 +39,336 @@ impl Representation {
     ) ->
         Representation
     {
+        // chars that make up a single grapheme end up in one glyph
+        let data: Vec<Vec<Glyph>> = data.iter()
+            .map(|line| glyph::segment(&line.iter().collect::<String>()))
+            .collect();
+
+        let styles = data.iter()
+            .map(|line| vec![Style::default(); line.len()])
+            .collect();
+
         Representation {
-            null_char: null_char,
-            data: data
+            null_char: Glyph::from_char(null_char),
+            data: data,
+            styles: styles,
+            animations: HashMap::new(),
+            source: None,
         }
     }
 
     pub fn from_file(
         filepath: &str
     ) ->
-        Result<Representation, Box<dyn Error>>
+        Result<Representation, AssetError>
     {
-        let contents =
-            fs::read_to_string(filepath).unwrap();
+        let invalid = |message: String| AssetError::Invalid {
+            path: filepath.to_string(),
+            message: message,
+        };
+
+        let contents = fs::read_to_string(filepath)
+            .map_err(|err| AssetError::Missing {
+                path: filepath.to_string(),
+                source: err,
+            })?;
+
+        let docs = YamlLoader::load_from_str(&contents)
+            .map_err(|err| AssetError::Yaml {
+                path: filepath.to_string(),
+                source: err,
+            })?;
+
+        let doc = match docs.first() {
+            Some(doc) => doc,
+            None => return Err(AssetError::MissingField {
+                path: filepath.to_string(),
+                field: "data".to_string(),
+            }),
+        };
+
+        let lines = block_lines(&contents);
+
+        let null_char_str = match &doc["null_char"] {
+            Yaml::BadValue => return Err(AssetError::MissingField {
+                path: filepath.to_string(),
+                field: "null_char".to_string(),
+            }),
+            Yaml::String(null_char) => null_char.clone(),
+            // one digit is a number to YAML
+            Yaml::Integer(null_char) => null_char.to_string(),
+            _ => String::new(),
+        };
+
+        let mut null_chars = null_char_str.chars();
+        let null_char_char = match (null_chars.next(), null_chars.next()) {
+            (Some(null_char), None) => null_char,
+            _ => return Err(AssetError::NullChar {
+                path: filepath.to_string(),
+                found: null_char_str,
+            }),
+        };
+
+        // style applied to every cell not covered by `colors`
+        let default_style = Style::from_yaml(&doc["style"])
+            .map_err(|err| invalid(err.to_string()))?;
+
+        let mut palette = HashMap::<char, Style>::new();
+
+        if let Some(entries) = doc["palette"].as_hash() {
+            for (key, value) in entries {
+                let key_str = match key {
+                    Yaml::String(key) => key.clone(),
+                    // one digit is a number to YAML
+                    Yaml::Integer(key) => key.to_string(),
+                    _ => String::new(),
+                };
+
+                // checked like `null_char`, so a second key starting
+                // with the same character can't take the first one's place
+                let mut key_chars = key_str.chars();
+                let key_char = match (key_chars.next(), key_chars.next()) {
+                    (Some(key_char), None) => key_char,
+                    _ => return Err(invalid(format!(
+                        "palette key {:?} must be a single character",
+                        key_str))),
+                };
+
+                palette.insert(key_char, Style::from_yaml(value)
+                    .map_err(|err| invalid(err.to_string()))?);
+            }
+        }
+
+        let data_str = doc["data"].as_str()
+            .ok_or_else(|| AssetError::MissingField {
+                path: filepath.to_string(),
+                field: "data".to_string(),
+            })?;
 
-        let doc =
-            &YamlLoader::load_from_str(&contents)?[0];
+        let mut representation = parse_frame(
+            filepath,
+            null_char_char,
+            data_str,
+            doc["colors"].as_str(),
+            default_style,
+            &palette,
+            &lines)?;
 
-        let data_str = doc["data"].as_str().unwrap();
-        let mut data_vec = Vec::<Vec<char>>::new();
+        // every frame shares the file's null char, style and palette
+        let mut animations = HashMap::<String, Animation>::new();
 
-        for part in data_str.split('\n') {
-            let line: Vec<char> =
-                part.chars().collect();
+        if let Some(entries) = doc["animations"].as_hash() {
+            for (name, frames_yaml) in entries {
+                let name = name.as_str()
+                    .ok_or_else(|| invalid(
+                        "animation names must be strings".to_string()))?;
 
-            data_vec.push(line);
+                // with no frames an animation would be over
+                // before anything of it has been drawn
+                let frames_yaml = frames_yaml.as_vec()
+                    .filter(|frames_yaml| !frames_yaml.is_empty())
+                    .ok_or_else(|| invalid(format!(
+                        "animation {} must be a list of at least one frame",
+                        name)))?;
+
+                let mut frames = Vec::<Representation>::new();
+                let mut durations = Vec::<f32>::new();
+
+                for frame_yaml in frames_yaml {
+                    let frame_data = frame_yaml["data"].as_str()
+                        .ok_or_else(|| AssetError::MissingField {
+                            path: filepath.to_string(),
+                            field: format!("data in a frame of {}", name),
+                        })?;
+
+                    let duration = yaml::as_f32(&frame_yaml["duration"])
+                        .filter(|d| *d > 0.0)
+                        .ok_or_else(|| invalid(format!(
+                            "every frame of {} needs a duration in seconds",
+                            name)))?;
+
+                    frames.push(parse_frame(
+                        filepath,
+                        null_char_char,
+                        frame_data,
+                        frame_yaml["colors"].as_str(),
+                        default_style,
+                        &palette,
+                        &lines)?);
+                    durations.push(duration);
+                }
+
+                animations.insert(
+                    name.to_string(), Animation::new(frames, durations));
+            }
         }
 
-        let null_char_str =
-            doc["null_char"].as_str().unwrap();
-        let null_char_char =
-            null_char_str.chars().next().unwrap();
+        representation.animations = animations;
+        representation.source = Some(filepath.to_string());
+
+        Ok(representation)
+    }
+
+    pub fn with_style(mut self, style: Style) -> Representation {
+        for line in self.styles.iter_mut() {
+            for cell in line.iter_mut() {
+                *cell = style;
+            }
+        }
 
-        Ok(Representation {
-            null_char: null_char_char,
-            data: data_vec
-        })
+        self
     }
 
-    pub fn null_char(&self) -> char {
+    pub fn null_char(&self) -> Glyph {
         (*self).null_char
     }
 
-    pub fn data(&self) -> &Vec<Vec<char>> {
+    pub fn data(&self) -> &Vec<Vec<Glyph>> {
         &(*self).data
     }
+
+    pub fn styles(&self) -> &Vec<Vec<Style>> {
+        &(*self).styles
+    }
+
+    pub fn animation(&self, name: &str) -> Option<&Animation> {
+        (*self).animations.get(name)
+    }
+
+    pub fn animations(&self) -> &HashMap<String, Animation> {
+        &(*self).animations
+    }
+
+    pub fn source(&self) -> Option<&str> {
+        (*self).source.as_deref()
+    }
+}
+
+// a block of `data` and the `colors` going with it,
+// `lines` tells where in the file `data` is
+fn parse_frame(
+    filepath: &str,
+    null_char: char,
+    data_str: &str,
+    colors_str: Option<&str>,
+    default_style: Style,
+    palette: &HashMap<char, Style>,
+    lines: &[(String, usize)]
+) ->
+    Result<Representation, AssetError>
+{
+    let mut data_vec = Vec::<Vec<Glyph>>::new();
+
+    // blocks that aren't last in the file end with a newline
+    for part in data_str.lines() {
+        data_vec.push(glyph::segment(part));
+    }
+
+    if data_vec.is_empty() || data_vec[0].is_empty() {
+        return Err(AssetError::MissingField {
+            path: filepath.to_string(),
+            field: "data".to_string(),
+        });
+    }
+
+    // everything assumes the rows are as wide as the first one
+    for i in 1..data_vec.len() {
+        if data_vec[i].len() != data_vec[0].len() {
+            let line = lines.iter()
+                .find(|(block, _)| block == data_str)
+                .map(|(_, line)| line + i);
+
+            return Err(AssetError::RaggedRows {
+                path: filepath.to_string(),
+                line: line,
+                row: i + 1,
+                width: data_vec[i].len(),
+                expected: data_vec[0].len(),
+            });
+        }
+    }
+
+    // `colors` mirrors `data` line by line, every char is a palette
+    // key for a column, spaces keep the default style
+    let colors_vec: Vec<Vec<char>> = match colors_str {
+        Some(colors_str) => colors_str.lines()
+            .map(|part| part.chars().collect())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut styles_vec = Vec::<Vec<Style>>::new();
+
+    for i in 0..data_vec.len() {
+        let mut line = Vec::<Style>::new();
+
+        for j in 0..data_vec[i].len() {
+            // both halves of a wide glyph look the same
+            if data_vec[i][j].is_continuation() && j > 0 {
+                line.push(line[j - 1]);
+                continue;
+            }
+
+            let key = colors_vec.get(i)
+                .and_then(|colors_line| colors_line.get(j));
+
+            let style = match key {
+                None | Some(' ') => default_style,
+                Some(k) => match palette.get(k) {
+                    Some(style) => *style,
+                    None => {
+                        return Err(AssetError::Invalid {
+                            path: filepath.to_string(),
+                            message: format!(
+                                "color key {:?} is not in palette", k),
+                        });
+                    }
+                },
+            };
+
+            line.push(style);
+        }
+
+        styles_vec.push(line);
+    }
+
+    Ok(Representation {
+        null_char: Glyph::from_char(null_char),
+        data: data_vec,
+        styles: styles_vec,
+        animations: HashMap::new(),
+        source: None,
+    })
+}
+
+// every `|` block in the file along with the line its first row is
+// on, the loaded document doesn't know where anything came from
+fn block_lines(contents: &str) -> Vec<(String, usize)> {
+    let mut blocks = BlockLines {
+        blocks: Vec::new(),
+    };
+
+    // the file has been loaded once already, so it parses
+    let _ = Parser::new(contents.chars()).load(&mut blocks, false);
+
+    blocks.blocks
+}
+
+struct BlockLines {
+    blocks: Vec<(String, usize)>,
+}
+
+impl MarkedEventReceiver for BlockLines {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if let Event::Scalar(value, TScalarStyle::Literal, _, _) = event {
+            // the mark is on the first row that isn't empty
+            let empty = value.chars()
+                .take_while(|ch| *ch == '\n')
+                .count();
+
+            let line = mark.line().saturating_sub(empty);
+
+            (*self).blocks.push((value, line));
+        }
+    }
 }
@@ -1,54 +1,119 @@
 use std::error::Error;
-use std::io::Stdout;
-use std::io::Write;
 
-use crossterm::Output;
-use crossterm::QueueableCommand;
-use crossterm::cursor;
-use crossterm::input::AsyncReader;
 use crossterm::input::InputEvent;
-use crossterm::screen::RawScreen;
+use crossterm::input::KeyEvent;
 
+use crate::recording::Replay;
 use crate::renderer::types;
+use crate::renderer::types::Backend;
+use crate::renderer::types::Glyph;
+use crate::renderer::types::Style;
+use crate::renderer::types::TerminalBackend;
 
-pub struct Renderer {
-    _raw: RawScreen,
-    stdout: Stdout,
-    old_frame: Vec<Vec<char>>,
-    reader: AsyncReader,
+#[derive(Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub glyph: Glyph,
+    pub style: Style,
 }
 
-impl Renderer {
-    pub fn new(
-        raw: RawScreen, reader: AsyncReader,
-        win_width: u16, win_height: u16,
-        stdout: Stdout
-    ) -> Renderer {
-        let width = win_width as usize;
-        let height = win_height as usize;
+impl Cell {
+    pub fn blank() -> Cell {
+        Cell {
+            glyph: Glyph::from_char(' '),
+            style: Style::default()
+        }
+    }
+
+    // what the terminal may be showing anywhere nothing has been
+    // drawn yet, it differs from any cell so all of them get drawn
+    fn unknown() -> Cell {
+        Cell {
+            glyph: Glyph::from_char('\0'),
+            style: Style::default()
+        }
+    }
+}
+
+// output of the renderer so far, for profiling
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Stats {
+    // frames rendered
+    pub frames: u64,
+    // bytes sent to the backend over all frames
+    pub bytes: u64,
+    // bytes sent for the last frame
+    pub last_bytes: usize,
+    // bytes sent for the biggest frame
+    pub max_bytes: usize,
+}
+
+impl Stats {
+    pub fn average_bytes(&self) -> u64 {
+        if (*self).frames == 0 {
+            return 0;
+        }
+
+        (*self).bytes / (*self).frames
+    }
+}
+
+pub struct Renderer<B: Backend = TerminalBackend> {
+    backend: B,
+    // size of the backend at the last frame
+    size: (u16, u16),
+    old_frame: Vec<Vec<Cell>>,
+    // style the backend is currently set to
+    style: Style,
+    // where the backend's cursor is, `None` when it isn't known
+    cursor: Option<(usize, usize)>,
+    stats: Stats,
+    // number of `events` calls so far
+    frame: u64,
+    replay: Option<Replay>,
+}
 
+impl<B: Backend> Renderer<B> {
+    // the frame takes the size of the backend on the first render
+    pub fn new(backend: B) -> Renderer<B> {
         Renderer {
-            _raw: raw,
-            reader: reader,
-            stdout: stdout,
-            old_frame: vec![vec![' '; height]; width],
+            backend: backend,
+            size: (0, 0),
+            old_frame: Vec::new(),
+            style: Style::default(),
+            cursor: None,
+            stats: Stats::default(),
+            frame: 0,
+            replay: None,
         }
     }
 
     pub fn render(
         &mut self,
-        objects: &Vec<(&types::Location, &types::Representation)>
+        objects: &[(types::Layer, &types::Location, &types::Representation)]
     ) ->
         Result<(), Box<dyn Error>>
     {
+        // the terminal may have been resized since the last frame
+        let size = (*self).backend.size()?;
+        if size != (*self).size {
+            (*self).resize(size);
+        }
+
         // frame width, frame height
-        let fwidth = (*self).old_frame.len();
-        let fheight = (*self).old_frame[0].len();
+        let fwidth = (*self).size.0 as usize;
+        let fheight = (*self).size.1 as usize;
 
         let mut new_frame =
-            vec![vec![' '; fheight]; fwidth];
+            vec![vec![Cell::blank(); fheight]; fwidth];
+
+        // the sort is stable, objects on the same
+        // layer are drawn in the order they were given
+        let mut order: Vec<usize> = (0..objects.len()).collect();
+        order.sort_by_key(|k| objects[*k].0);
+
+        for k in order {
+            let o = (objects[k].1, objects[k].2);
 
-        for o in objects {
             // object width, object height
             let owidth = o.1.data().len();
             let oheight = o.1.data()[0].len();
@@ -58,54 +123,309 @@ impl Renderer {
                     // null_char
                     let nc = o.1.null_char();
 
+                    let glyph = o.1.data()[i][j];
+
+                    // right halves of wide glyphs
+                    // are drawn along with the left ones
+                    if glyph == nc || glyph.is_continuation() {
+                        continue;
+                    }
+
                     // positions relative to frame
                     // "frame_x", "frame_y"
                     let fx = o.0.x + j as i32;
                     let fy = o.0.y + i as i32;
 
                     if
-                        o.1.data()[i][j] != nc &&
                         fx >= 0 && fx < fwidth as i32 &&
                         fy >= 0 && fy < fheight as i32
                     {
                         let fx_u = fx as usize;
                         let fy_u = fy as usize;
 
-                        new_frame[fx_u][fy_u] =
-                            o.1.data()[i][j];
+                        let cell = Cell {
+                            glyph: glyph,
+                            style: o.1.styles()[i][j],
+                        };
+
+                        if glyph.width() < 2 {
+                            split(&mut new_frame, fx_u, fy_u);
+                            new_frame[fx_u][fy_u] = cell;
+                        } else if fx_u + 1 < fwidth {
+                            split(&mut new_frame, fx_u, fy_u);
+                            split(&mut new_frame, fx_u + 1, fy_u);
+                            new_frame[fx_u][fy_u] = cell;
+                            new_frame[fx_u + 1][fy_u] = Cell {
+                                glyph: Glyph::continuation(),
+                                ..cell
+                            };
+                        } else {
+                            // half a glyph doesn't fit on the terminal
+                            split(&mut new_frame, fx_u, fy_u);
+                            new_frame[fx_u][fy_u] = Cell {
+                                glyph: Glyph::from_char(' '),
+                                ..cell
+                            };
+                        }
                     }
                 }
             }
         }
 
-        for x in 0..fwidth {
-            for y in 0..fheight {
-                if (*self).old_frame[x][y] != new_frame[x][y] {
-                    (*self).stdout.queue(
-                        cursor::MoveTo(
-                            x as u16, y as u16)
-                    )?;
-
-                    (*self).stdout.queue(
-                        Output(new_frame[x][y])
-                    )?;
+        for y in 0..fheight {
+            let mut x = 0;
+
+            while x < fwidth {
+                if (*self).old_frame[x][y] == new_frame[x][y] {
+                    x += 1;
+                    continue;
                 }
+
+                // wide glyphs are printed whole
+                if new_frame[x][y].glyph.is_continuation() && x > 0 {
+                    x -= 1;
+                }
+
+                // every run of changed cells on a row takes a single
+                // move, and none if the cursor is there already
+                if (*self).cursor != Some((x, y)) {
+                    (*self).backend.move_to(x as u16, y as u16)?;
+                }
+
+                let mut text = String::new();
+
+                while
+                    x < fwidth &&
+                    (*self).old_frame[x][y] != new_frame[x][y]
+                {
+                    let cell = new_frame[x][y];
+
+                    if (*self).style != cell.style {
+                        if !text.is_empty() {
+                            (*self).backend.print(&text)?;
+                            text.clear();
+                        }
+
+                        (*self).backend.set_style(cell.style)?;
+                        (*self).style = cell.style;
+                    }
+
+                    text.push_str(cell.glyph.as_str());
+                    x += cell.glyph.width().max(1);
+                }
+
+                (*self).backend.print(&text)?;
+
+                // past the last column terminals don't agree
+                // on where the cursor ends up
+                (*self).cursor =
+                    if x < fwidth { Some((x, y)) } else { None };
             }
         }
 
-        (*self).stdout.flush()?;
+        let written = (*self).backend.flush()?;
+
+        (*self).stats.frames += 1;
+        (*self).stats.bytes += written as u64;
+        (*self).stats.last_bytes = written;
+        (*self).stats.max_bytes = (*self).stats.max_bytes.max(written);
+
         (*self).old_frame = new_frame;
 
         Ok(())
     }
 
+    // whatever was on the screen can't be trusted
+    // after a resize, so the next frame is drawn in full
+    fn resize(&mut self, size: (u16, u16)) {
+        (*self).size = size;
+        (*self).redraw();
+    }
+
+    // draws the next frame in full instead of only what changed,
+    // for when something else has messed with the terminal
+    pub fn redraw(&mut self) {
+        let width = (*self).size.0 as usize;
+        let height = (*self).size.1 as usize;
+
+        (*self).old_frame =
+            vec![vec![Cell::unknown(); height]; width];
+        (*self).cursor = None;
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &(*self).stats
+    }
+
+    // size the next frame is drawn at
+    pub fn size(&self) -> Result<(u16, u16), Box<dyn Error>> {
+        (*self).backend.size()
+    }
+
+    // events for the next frame, while a replay is running the
+    // backend's own input is thrown away except for Ctrl-C
     pub fn events(&mut self) -> Vec<InputEvent> {
-        let mut events = Vec::new();
+        let frame = (*self).frame;
+        (*self).frame += 1;
 
-        while let Some(event) = (*self).reader.next() {
-            events.push(event);
+        let events = (*self).backend.events();
+
+        match (*self).replay.as_mut() {
+            Some(replay) if !replay.is_finished() => {
+                let mut replayed = replay.events(frame);
+
+                let quit = InputEvent::Keyboard(KeyEvent::Ctrl('c'));
+                if events.contains(&quit) {
+                    replayed.push(quit);
+                }
+
+                replayed
+            }
+            _ => events,
         }
+    }
+
+    pub fn replay(&mut self, replay: Replay) {
+        (*self).replay = Some(replay);
+    }
+
+    pub fn backend(&self) -> &B {
+        &(*self).backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut (*self).backend
+    }
+}
+
+// whatever is left of a wide glyph `x` is part of is blanked,
+// before `x` gets something else
+fn split(frame: &mut [Vec<Cell>], x: usize, y: usize) {
+    let glyph = frame[x][y].glyph;
+
+    if glyph.is_continuation() && x > 0 {
+        frame[x - 1][y].glyph = Glyph::from_char(' ');
+    }
+
+    if glyph.width() == 2 && x + 1 < frame.len() {
+        frame[x + 1][y].glyph = Glyph::from_char(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::input::InputEvent;
+    use crossterm::input::KeyEvent;
+
+    use crate::renderer::types::Layer;
+    use crate::renderer::types::Location;
+    use crate::renderer::types::MemoryBackend;
+    use crate::renderer::types::Renderer;
+    use crate::renderer::types::Representation;
+
+    fn object(null_char: char, lines: &[&str]) -> Representation {
+        Representation::new(
+            null_char,
+            lines.iter().map(|line| line.chars().collect()).collect())
+    }
+
+    fn snapshot(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    #[test]
+    fn draws_objects_at_their_locations() {
+        let mut renderer = Renderer::new(MemoryBackend::new(8, 4));
+
+        let box_view = object('.', &["+-+", "|.|", "+-+"]);
+        let box_location = Location { x: 1, y: 1 };
+        // partly off the frame
+        let dot_view = object('.', &["ab"]);
+        let dot_location = Location { x: 7, y: 0 };
+
+        renderer.render(&[
+            (Layer::Gameplay, &box_location, &box_view),
+            (Layer::Gameplay, &dot_location, &dot_view),
+        ]).unwrap();
+
+        assert_eq!(renderer.backend().snapshot(), snapshot(&[
+            "       a",
+            " +-+    ",
+            " | |    ",
+            " +-+    ",
+        ]));
+    }
+
+    #[test]
+    fn later_layers_cover_earlier_ones() {
+        let mut renderer = Renderer::new(MemoryBackend::new(6, 2));
+
+        let hud_view = object('.', &["HUD"]);
+        let hud_location = Location { x: 0, y: 0 };
+        let ship_view = object('.', &["ship", "ship"]);
+        let ship_location = Location { x: 1, y: 0 };
+
+        // pushed last, but under the hud
+        renderer.render(&[
+            (Layer::Hud, &hud_location, &hud_view),
+            (Layer::Gameplay, &ship_location, &ship_view),
+        ]).unwrap();
+
+        assert_eq!(renderer.backend().snapshot(), snapshot(&[
+            "HUDip ",
+            " ship ",
+        ]));
+    }
+
+    #[test]
+    fn clears_what_moved_away() {
+        let mut renderer = Renderer::new(MemoryBackend::new(6, 2));
+
+        let ship_view = object('.', &["<=>"]);
+
+        renderer.render(&[
+            (Layer::Gameplay, &Location { x: 0, y: 0 }, &ship_view),
+        ]).unwrap();
+        renderer.render(&[
+            (Layer::Gameplay, &Location { x: 2, y: 1 }, &ship_view),
+        ]).unwrap();
+
+        assert_eq!(renderer.backend().snapshot(), snapshot(&[
+            "      ",
+            "  <=> ",
+        ]));
+    }
+
+    #[test]
+    fn wide_glyphs_take_two_columns() {
+        let mut renderer = Renderer::new(MemoryBackend::new(6, 2));
+
+        let wide_view = object('.', &["a世b..", "世世世"]);
+        let wide_location = Location { x: 0, y: 0 };
+        // covers the right half of the second glyph on the second line
+        let cover_view = object('.', &["x"]);
+        let cover_location = Location { x: 3, y: 1 };
+
+        renderer.render(&[
+            (Layer::Gameplay, &wide_location, &wide_view),
+            (Layer::Hud, &cover_location, &cover_view),
+        ]).unwrap();
+
+        assert_eq!(renderer.backend().snapshot(), snapshot(&[
+            "a世b  ",
+            "世 x世",
+        ]));
+    }
+
+    #[test]
+    fn scripted_events_come_on_their_frame() {
+        let mut renderer = Renderer::new(MemoryBackend::new(1, 1));
+
+        let enter = InputEvent::Keyboard(KeyEvent::Enter);
+        renderer.backend_mut().script(1, enter.clone());
 
-        events
+        assert!(renderer.events().is_empty());
+        assert_eq!(renderer.events(), vec![enter]);
+        assert!(renderer.events().is_empty());
     }
 }
@@ -0,0 +1,96 @@
+use std::error::Error;
+
+use crossterm::style::Color;
+use yaml_rust::Yaml;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub fn fg(color: Color) -> Style {
+        Style {
+            fg: Some(color),
+            ..Style::default()
+        }
+    }
+
+    // expects a mapping like
+    // `{ fg: red, bg: black, attrs: [bold, reverse] }`,
+    // every key is optional
+    pub fn from_yaml(
+        doc: &Yaml
+    ) ->
+        Result<Style, Box<dyn Error>>
+    {
+        let mut style = Style::default();
+
+        if !doc["fg"].is_badvalue() {
+            style.fg = Some(parse_color(&doc["fg"])?);
+        }
+
+        if !doc["bg"].is_badvalue() {
+            style.bg = Some(parse_color(&doc["bg"])?);
+        }
+
+        if let Some(attrs) = doc["attrs"].as_vec() {
+            for attr in attrs {
+                match attr.as_str() {
+                    Some("bold") => style.bold = true,
+                    Some("dim") => style.dim = true,
+                    Some("reverse") => style.reverse = true,
+                    _ => {
+                        return Err(format!(
+                            "unknown attribute {:?}", attr).into());
+                    }
+                }
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+fn parse_color(
+    value: &Yaml
+) ->
+    Result<Color, Box<dyn Error>>
+{
+    let name = match value.as_str() {
+        Some(name) => name,
+        None => {
+            return Err(format!(
+                "color must be a string, got {:?}", value).into());
+        }
+    };
+
+    let color = match name.to_lowercase().as_ref() {
+        "black" => Color::Black,
+        "dark_grey" => Color::DarkGrey,
+        "red" => Color::Red,
+        "dark_red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark_green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark_yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark_blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark_magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark_cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" => Color::Grey,
+        _ => {
+            return Err(format!(
+                "unknown color {:?}", name).into());
+        }
+    };
+
+    Ok(color)
+}
@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::io::Stdout;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use crossterm::Output;
+use crossterm::QueueableCommand;
+use crossterm::cursor;
+use crossterm::input::AsyncReader;
+use crossterm::input::InputEvent;
+use crossterm::input::KeyEvent;
+use crossterm::screen::RawScreen;
+use crossterm::style::Attribute;
+use crossterm::style::SetAttribute;
+use crossterm::style::SetBackgroundColor;
+use crossterm::style::SetForegroundColor;
+use crossterm::terminal;
+
+use crate::renderer;
+use crate::renderer::types::Backend;
+use crate::renderer::types::Style;
+
+pub struct TerminalBackend {
+    _raw: RawScreen,
+    stdout: Stdout,
+    // everything for the frame, written out at once on flush
+    buffer: Vec<u8>,
+    reader: AsyncReader,
+    // set by SIGINT and SIGTERM
+    terminated: Arc<AtomicBool>,
+}
+
+impl TerminalBackend {
+    pub fn new(
+        raw: RawScreen, reader: AsyncReader,
+        stdout: Stdout, terminated: Arc<AtomicBool>
+    ) -> TerminalBackend {
+        TerminalBackend {
+            _raw: raw,
+            reader: reader,
+            stdout: stdout,
+            buffer: Vec::new(),
+            terminated: terminated,
+        }
+    }
+}
+
+impl Drop for TerminalBackend {
+    fn drop(&mut self) {
+        renderer::restore();
+    }
+}
+
+impl Backend for TerminalBackend {
+    fn move_to(
+        &mut self, x: u16, y: u16
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        (*self).buffer.queue(cursor::MoveTo(x, y))?;
+
+        Ok(())
+    }
+
+    fn set_style(
+        &mut self, style: Style
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        // reset clears both colors and attributes,
+        // so everything set below starts from scratch
+        (*self).buffer.queue(SetAttribute(Attribute::Reset))?;
+
+        if style.bold {
+            (*self).buffer.queue(SetAttribute(Attribute::Bold))?;
+        }
+
+        if style.dim {
+            (*self).buffer.queue(SetAttribute(Attribute::Dim))?;
+        }
+
+        if style.reverse {
+            (*self).buffer.queue(SetAttribute(Attribute::Reverse))?;
+        }
+
+        if let Some(color) = style.fg {
+            (*self).buffer.queue(SetForegroundColor(color))?;
+        }
+
+        if let Some(color) = style.bg {
+            (*self).buffer.queue(SetBackgroundColor(color))?;
+        }
+
+        Ok(())
+    }
+
+    fn print(
+        &mut self, text: &str
+    ) ->
+        Result<(), Box<dyn Error>>
+    {
+        (*self).buffer.queue(Output(text))?;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<usize, Box<dyn Error>> {
+        let written = (*self).buffer.len();
+
+        (*self).stdout.write_all(&(*self).buffer)?;
+        (*self).stdout.flush()?;
+        (*self).buffer.clear();
+
+        Ok(written)
+    }
+
+    fn size(&self) -> Result<(u16, u16), Box<dyn Error>> {
+        Ok(terminal::size()?)
+    }
+
+    fn events(&mut self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+
+        for event in &mut (*self).reader {
+            events.push(event);
+        }
+
+        // the game treats it as Ctrl-C, which quits right away
+        if (*self).terminated.swap(false, Ordering::SeqCst) {
+            events.push(InputEvent::Keyboard(KeyEvent::Ctrl('c')));
+        }
+
+        events
+    }
+}
@@ -0,0 +1,108 @@
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::renderer::types::Representation;
+
+pub struct Flake {
+    pub xf: f32,
+    pub yf: f32,
+    // position before the last update, used to interpolate
+    pub prev_yf: f32,
+}
+
+pub struct Layer {
+    pub view: usize,
+    // cells per second
+    pub speed: f32,
+    pub flakes: Vec<Flake>,
+}
+
+// layers of flakes falling down the playfield,
+// nearer layers fall faster
+pub struct Starfield {
+    width: f32,
+    height: f32,
+    layers: Vec<Layer>,
+    // separate from the game's, so the background
+    // doesn't change how the game plays out
+    rng: StdRng,
+}
+
+impl Starfield {
+    pub fn new(width: f32, height: f32, seed: u64) -> Starfield {
+        Starfield {
+            width: width,
+            height: height,
+            layers: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // flakes start spread over the whole playfield
+    pub fn add_layer(
+        &mut self, views: &[Representation], view: usize,
+        density: usize, speed: f32
+    ) {
+        let size = view_size(&views[view]);
+        let mut flakes = Vec::<Flake>::new();
+
+        for _i in 0..density {
+            let x = (*self).random_x(size.0);
+            let y = (*self).rng.gen_range(-size.1, (*self).height);
+
+            flakes.push(Flake {
+                xf: x,
+                yf: y,
+                prev_yf: y,
+            });
+        }
+
+        (*self).layers.push(Layer {
+            view: view,
+            speed: speed,
+            flakes: flakes,
+        });
+    }
+
+    // sizes are read from the views every time, they
+    // may have been reloaded with a different one
+    pub fn update(&mut self, views: &[Representation], dt: f32) {
+        for i in 0..(*self).layers.len() {
+            let size = view_size(&views[(*self).layers[i].view]);
+
+            for j in 0..(*self).layers[i].flakes.len() {
+                let layer_ptr = &mut (*self).layers[i];
+                let flake_ptr = &mut (*layer_ptr).flakes[j];
+
+                (*flake_ptr).prev_yf = (*flake_ptr).yf;
+                (*flake_ptr).yf += (*layer_ptr).speed * dt;
+
+                // gone off the bottom, comes back in at the top
+                if (*flake_ptr).yf >= (*self).height {
+                    let x = (*self).random_x(size.0);
+
+                    let flake_ptr = &mut (*self).layers[i].flakes[j];
+                    (*flake_ptr).xf = x;
+                    (*flake_ptr).yf -= (*self).height + size.1;
+                    (*flake_ptr).prev_yf = (*flake_ptr).yf;
+                }
+            }
+        }
+    }
+
+    pub fn layers(&self) -> &Vec<Layer> {
+        &(*self).layers
+    }
+
+    fn random_x(&mut self, width: f32) -> f32 {
+        let max = ((*self).width - width).max(1.0);
+
+        (*self).rng.gen_range(0.0, max).floor()
+    }
+}
+
+// so flakes leave the screen completely
+fn view_size(view: &Representation) -> (f32, f32) {
+    (view.data()[0].len() as f32, view.data().len() as f32)
+}
@@ -0,0 +1,280 @@
+use std::error::Error;
+use std::slice;
+use std::time::Duration;
+
+use crossterm::input::InputEvent;
+use crossterm::input::KeyEvent;
+use crossterm::style::Color;
+
+use crate::gameloop;
+use crate::gameloop::SystemClock;
+use crate::gameloop::Timestep;
+use crate::hotreload::Watcher;
+use crate::renderer;
+use crate::renderer::types::AnimationPlayer;
+use crate::renderer::types::Backend;
+use crate::renderer::types::Layer;
+use crate::renderer::types::Location;
+use crate::renderer::types::Renderer;
+use crate::renderer::types::Representation;
+use crate::renderer::types::Style;
+
+// shows a single object file the way the game draws it,
+// for working on it without starting the game
+struct Viewer {
+    filepath: String,
+    object: Representation,
+    watcher: Watcher,
+    // 0 is the object as it is, the rest are
+    // its animations sorted by name
+    animation: usize,
+    player: AnimationPlayer,
+    paused: bool,
+    // why the file couldn't be reloaded
+    error: Option<String>,
+    // the next frame has to be drawn in full
+    redraw: bool,
+}
+
+pub fn view(filepath: &str, fps: u8) -> Result<(), Box<dyn Error>> {
+    // a broken file is reported before the terminal is taken over
+    let object = Representation::from_file(filepath)?;
+
+    let watcher = Watcher::new(slice::from_ref(&object));
+
+    let state = Viewer {
+        filepath: filepath.to_string(),
+        object: object,
+        watcher: watcher,
+        animation: 0,
+        player: AnimationPlayer::still(),
+        paused: false,
+        error: None,
+        redraw: false,
+    };
+
+    let mut renderer = renderer::init()?;
+    let mut clock = SystemClock::new();
+
+    gameloop::gameloop(
+        fps, Timestep::Variable, &mut clock, state, &mut renderer,
+        None, input, update, render)?;
+
+    Ok(())
+}
+
+fn input(
+    state: &mut Viewer,
+    inputs: Vec<InputEvent>,
+    proceed: &mut bool
+) {
+    for event in inputs {
+        let key = match event {
+            InputEvent::Keyboard(key) => key,
+            _ => continue,
+        };
+
+        let count = (*state).object.animations().len() + 1;
+
+        // letters work regardless of shift and caps lock,
+        // like they do in the game
+        match key {
+            KeyEvent::Ctrl('c') | KeyEvent::Esc |
+            KeyEvent::Char('q') | KeyEvent::Char('Q') => {
+                *proceed = false;
+                break;
+            }
+            KeyEvent::Ctrl('l') => {
+                (*state).redraw = true;
+            }
+            KeyEvent::Right | KeyEvent::Char('d') | KeyEvent::Char('D') => {
+                (*state).animation = ((*state).animation + 1) % count;
+                play(state);
+            }
+            KeyEvent::Left | KeyEvent::Char('a') | KeyEvent::Char('A') => {
+                (*state).animation =
+                    ((*state).animation + count - 1) % count;
+                play(state);
+            }
+            KeyEvent::Char(' ') => {
+                (*state).paused = !(*state).paused;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update(state: &mut Viewer, delta: Duration) {
+    let dt = delta.as_secs_f32();
+
+    // the animation shown, by name, before the file changed
+    let name = animation_name(state);
+
+    let reloads = (*state).watcher.update(
+        slice::from_mut(&mut (*state).object), dt);
+
+    for reload in reloads {
+        match reload {
+            Ok(_) => reloaded(state, name.clone()),
+            Err(err) => (*state).error = Some(err.to_string()),
+        }
+    }
+
+    if !(*state).paused {
+        (*state).player.update(&(*state).object, dt);
+    }
+}
+
+// a broken file keeps the last good version on the screen,
+// once it's fixed the viewer stays on the same animation
+// if it's still there
+fn reloaded(state: &mut Viewer, name: Option<String>) {
+    (*state).error = None;
+
+    let names = animation_names(&(*state).object);
+
+    (*state).animation = match name {
+        Some(name) => names.iter()
+            .position(|other| *other == name)
+            .map_or(0, |i| i + 1),
+        None => 0,
+    };
+
+    play(state);
+}
+
+fn play(state: &mut Viewer) {
+    match animation_name(state) {
+        Some(name) => (*state).player.play(&name, true),
+        None => (*state).player.stop(),
+    }
+}
+
+fn animation_name(state: &Viewer) -> Option<String> {
+    if (*state).animation == 0 {
+        return None;
+    }
+
+    animation_names(&(*state).object)
+        .get((*state).animation - 1)
+        .cloned()
+}
+
+fn animation_names(object: &Representation) -> Vec<String> {
+    let mut names: Vec<String> =
+        object.animations().keys().cloned().collect();
+    names.sort();
+
+    names
+}
+
+fn render<B: Backend>(
+    state: &mut Viewer,
+    renderer: &mut Renderer<B>,
+    _alpha: f32
+) ->
+    Result<(), Box<dyn Error>>
+{
+    if (*state).redraw {
+        renderer.redraw();
+        (*state).redraw = false;
+    }
+
+    let (width, height) = renderer.size()?;
+
+    let frame = (*state).player.frame(&(*state).object);
+    let frame_width = frame.data()[0].len();
+    let frame_height = frame.data().len();
+
+    let mut status = match animation_name(state) {
+        Some(name) => {
+            let frames = (*state).object.animation(&name)
+                .map_or(0, |animation| animation.frames().len());
+
+            format!("{} - frame {}/{}",
+                name, ((*state).player.index() + 1).min(frames), frames)
+        }
+        None => "no animation".to_string(),
+    };
+    if (*state).paused {
+        status.push_str(" (paused)");
+    }
+
+    let header = [
+        (format!("{} - {}x{}",
+            (*state).filepath, frame_width, frame_height),
+            Style { bold: true, ..Style::fg(Color::Cyan) }),
+        (status, Style::fg(Color::White)),
+        ("Left/Right - animation, Space - pause, Q - quit".to_string(),
+            Style::fg(Color::DarkGrey)),
+    ];
+
+    let mut header_views = Vec::<Representation>::new();
+    let mut header_locations = Vec::<Location>::new();
+
+    for (i, (line, style)) in header.iter().enumerate() {
+        header_views.push(line_view(line, *style));
+        header_locations.push(Location { x: 1, y: i as i32 });
+    }
+
+    // the object in the middle of what's left under the header
+    let location = Location {
+        x: width as i32 / 2 - frame_width as i32 / 2,
+        y: (height as i32 + 4) / 2 - frame_height as i32 / 2,
+    };
+
+    let checkerboard = checkerboard(frame_width, frame_height);
+
+    let error_view = line_view(
+        (*state).error.as_deref().unwrap_or(""),
+        Style { bold: true, ..Style::fg(Color::Red) });
+    let error_location = Location { x: 1, y: height as i32 - 1 };
+
+    let mut render_queue =
+        Vec::<(Layer, &Location, &Representation)>::new();
+
+    // whatever is left of it where the object has null chars
+    render_queue.push((Layer::Background, &location, &checkerboard));
+    render_queue.push((Layer::Gameplay, &location, frame));
+
+    for i in 0..header_views.len() {
+        render_queue.push((
+            Layer::Hud,
+            &header_locations[i],
+            &header_views[i]));
+    }
+
+    if (*state).error.is_some() {
+        render_queue.push((Layer::Overlay, &error_location, &error_view));
+    }
+
+    renderer.render(&render_queue)?;
+
+    Ok(())
+}
+
+fn line_view(line: &str, style: Style) -> Representation {
+    Representation::new('\0', vec![line.chars().collect()])
+        .with_style(style)
+}
+
+// squares two columns wide, so they come out about square
+fn checkerboard(width: usize, height: usize) -> Representation {
+    let mut data = Vec::<Vec<char>>::new();
+
+    for i in 0..height {
+        let mut line = Vec::<char>::new();
+
+        for j in 0..width {
+            line.push(if (i + j / 2) % 2 == 0 { ' ' } else { '\0' });
+        }
+
+        data.push(line);
+    }
+
+    Representation::new('\0', data)
+        .with_style(Style {
+            bg: Some(Color::DarkGrey),
+            ..Style::default()
+        })
+}
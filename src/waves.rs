@@ -0,0 +1,315 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+
+use yaml_rust::Yaml;
+use yaml_rust::YamlLoader;
+
+use crate::yaml::as_f32;
+
+// positions are top left corners of sprites in playfield cells,
+// except for paths, see `Stage::paths`
+pub type Point = (f32, f32);
+
+// seconds the stage's title is shown before enemies fly in
+pub const STAGE_TITLE_TIME: f32 = 2.0;
+
+pub struct Group {
+    pub path: String,
+    // seconds after the stage starts
+    pub delay: f32,
+    // seconds between two enemies of the group
+    pub spacing: f32,
+    // formation slots as (column, row), in order of arrival
+    pub slots: Vec<(i32, i32)>,
+    // index into the enemy kinds the stages were loaded with
+    pub kind: usize,
+}
+
+pub struct Stage {
+    pub origin: Point,
+    // distance between neighbouring slots
+    pub cell: Point,
+    // how far the formation sways to each side
+    pub sway: f32,
+    // cells per second, both along paths and in dives
+    pub speed: f32,
+    // seconds between dive attacks
+    pub dive_interval: f32,
+    // cells per second of enemy shots
+    pub shot_speed: f32,
+    // seconds between two shots of a diving enemy
+    pub shot_interval: f32,
+    // relative to the playfield, (0, 0) puts a sprite in the top left
+    // corner and (1, 1) in the bottom right one, whatever its size
+    pub paths: HashMap<String, Vec<Point>>,
+    pub groups: Vec<Group>,
+}
+
+// stages are played in the order of their file names,
+// groups name one of `kinds` as their enemy
+pub fn load_stages(
+    dirpath: &str, kinds: &[&str]
+) ->
+    Result<Vec<Stage>, Box<dyn Error>>
+{
+    let mut filepaths = Vec::<String>::new();
+
+    for entry in fs::read_dir(dirpath)? {
+        let path = entry?.path();
+
+        if path.extension() == Some(OsStr::new("yaml")) {
+            filepaths.push(path.to_string_lossy().into_owned());
+        }
+    }
+
+    filepaths.sort();
+
+    let mut stages = Vec::<Stage>::new();
+
+    for filepath in filepaths.iter() {
+        stages.push(load_stage(filepath, kinds)?);
+    }
+
+    if stages.is_empty() {
+        return Err(format!("no stages in {}", dirpath).into());
+    }
+
+    Ok(stages)
+}
+
+fn load_stage(
+    filepath: &str, kinds: &[&str]
+) ->
+    Result<Stage, Box<dyn Error>>
+{
+    let contents = fs::read_to_string(filepath)?;
+    let docs = YamlLoader::load_from_str(&contents)?;
+    let doc = docs.first()
+        .ok_or_else(|| format!("{}: empty stage", filepath))?;
+
+    let number = |value: &Yaml, name: &str| {
+        as_f32(value).ok_or_else(|| format!(
+            "{}: {} must be a number", filepath, name))
+    };
+
+    // a zero would have things happen on every update
+    let positive = |value: &Yaml, name: &str| {
+        as_f32(value)
+            .filter(|number| *number > 0.0)
+            .ok_or_else(|| format!(
+                "{}: {} must be a number above 0", filepath, name))
+    };
+
+    let mut paths = HashMap::<String, Vec<Point>>::new();
+
+    if let Some(entries) = doc["paths"].as_hash() {
+        for (name, points) in entries {
+            let name = name.as_str()
+                .ok_or_else(|| format!(
+                    "{}: path names must be strings", filepath))?;
+
+            let points = as_points(points)
+                .ok_or_else(|| format!(
+                    "{}: path {} must be a list of [x, y]",
+                    filepath, name))?;
+
+            if points.is_empty() {
+                return Err(format!(
+                    "{}: path {} is empty", filepath, name).into());
+            }
+
+            // past the right edge there's the wall and the side panel
+            if points.iter().any(|(x, _)| *x > 1.0) {
+                return Err(format!(
+                    "{}: path {} goes past the right edge at x 1",
+                    filepath, name).into());
+            }
+
+            paths.insert(name.to_string(), points);
+        }
+    }
+
+    let mut groups = Vec::<Group>::new();
+
+    // a stage without enemies would be over before it starts
+    let entries = doc["groups"].as_vec()
+        .filter(|entries| !entries.is_empty())
+        .ok_or_else(|| format!(
+            "{}: groups must be a list of at least one group", filepath))?;
+
+    for group in entries {
+        let path = group["path"].as_str()
+            .ok_or_else(|| format!(
+                "{}: every group needs a path", filepath))?;
+
+        if !paths.contains_key(path) {
+            return Err(format!(
+                "{}: unknown path {}", filepath, path).into());
+        }
+
+        let kind = group["kind"].as_str()
+            .ok_or_else(|| format!(
+                "{}: every group needs a kind", filepath))?;
+
+        let kind = kinds.iter()
+            .position(|k| *k == kind)
+            .ok_or_else(|| format!(
+                "{}: unknown kind {}", filepath, kind))?;
+
+        let slots = as_points(&group["slots"])
+            .ok_or_else(|| format!(
+                "{}: slots must be a list of [column, row]", filepath))?
+            .iter()
+            .map(|(column, row)| (*column as i32, *row as i32))
+            .collect();
+
+        groups.push(Group {
+            path: path.to_string(),
+            delay: as_f32(&group["delay"]).unwrap_or(0.0),
+            spacing: match &group["spacing"] {
+                Yaml::BadValue => 0.5,
+                spacing => positive(spacing, "spacing")?,
+            },
+            slots: slots,
+            kind: kind,
+        });
+    }
+
+    let formation = &doc["formation"];
+
+    Ok(Stage {
+        origin: as_point(&formation["origin"])
+            .ok_or_else(|| format!(
+                "{}: formation origin must be [x, y]", filepath))?,
+        cell: as_point(&formation["cell"])
+            .ok_or_else(|| format!(
+                "{}: formation cell must be [width, height]", filepath))?,
+        sway: as_f32(&formation["sway"]).unwrap_or(0.0),
+        speed: positive(&doc["speed"], "speed")?,
+        dive_interval: positive(&doc["dive_interval"], "dive_interval")?,
+        shot_speed: number(&doc["shot_speed"], "shot_speed")?,
+        shot_interval: positive(&doc["shot_interval"], "shot_interval")?,
+        paths: paths,
+        groups: groups,
+    })
+}
+
+fn as_point(value: &Yaml) -> Option<Point> {
+    let pair = value.as_vec()?;
+
+    if pair.len() != 2 {
+        return None;
+    }
+
+    Some((as_f32(&pair[0])?, as_f32(&pair[1])?))
+}
+
+fn as_points(value: &Yaml) -> Option<Vec<Point>> {
+    value.as_vec()?.iter().map(as_point).collect()
+}
+
+// enemy waiting to fly in
+pub struct Spawn {
+    // seconds after the stage starts
+    pub at: f32,
+    pub slot: (i32, i32),
+    pub path: Vec<Point>,
+    pub kind: usize,
+}
+
+pub struct Wave {
+    pub stage: usize,
+    // how many times all stages have been cleared,
+    // every round is faster than the previous one
+    pub round: u32,
+    // seconds since the stage started, negative
+    // while the stage's title is shown
+    pub time: f32,
+    pub pending: VecDeque<Spawn>,
+    pub dive_timer: f32,
+}
+
+impl Wave {
+    pub fn new(
+        stages: &[Stage], stage: usize, round: u32
+    ) -> Wave {
+        let mut pending = Vec::<Spawn>::new();
+
+        for group in stages[stage].groups.iter() {
+            let path = &stages[stage].paths[&group.path];
+
+            for (i, slot) in group.slots.iter().enumerate() {
+                pending.push(Spawn {
+                    at: group.delay + group.spacing * i as f32,
+                    slot: *slot,
+                    path: path.clone(),
+                    kind: group.kind,
+                });
+            }
+        }
+
+        pending.sort_by(|a, b| a.at.partial_cmp(&b.at)
+            .unwrap_or(Ordering::Equal));
+
+        Wave {
+            stage: stage,
+            round: round,
+            time: -STAGE_TITLE_TIME,
+            pending: pending.into_iter().collect(),
+            dive_timer: stages[stage].dive_interval,
+        }
+    }
+
+    pub fn next(&self, stages: &[Stage]) -> Wave {
+        let stage = ((*self).stage + 1) % stages.len();
+        let round =
+            if stage == 0 { (*self).round + 1 } else { (*self).round };
+
+        Wave::new(stages, stage, round)
+    }
+
+    // counts on across rounds, as shown to the player
+    pub fn number(&self, stages: &[Stage]) -> usize {
+        (*self).round as usize * stages.len() + (*self).stage + 1
+    }
+
+    pub fn speed(&self, stages: &[Stage]) -> f32 {
+        stages[(*self).stage].speed * (1.0 + 0.25 * (*self).round as f32)
+    }
+
+    // where a slot is right now, the whole formation
+    // sways from side to side
+    pub fn slot_position(
+        &self, stages: &[Stage], slot: (i32, i32)
+    ) -> Point {
+        let stage = &stages[(*self).stage];
+        let sway = stage.sway * ((*self).time.max(0.0) * 0.8).sin();
+
+        (
+            stage.origin.0 + stage.cell.0 * slot.0 as f32 + sway,
+            stage.origin.1 + stage.cell.1 * slot.1 as f32,
+        )
+    }
+}
+
+// moves `pos` at most `dist` towards `target`,
+// returns whether the target has been reached
+pub fn fly_towards(pos: &mut Point, target: Point, dist: f32) -> bool {
+    let dx = target.0 - pos.0;
+    let dy = target.1 - pos.1;
+    let left = (dx * dx + dy * dy).sqrt();
+
+    if left <= dist {
+        *pos = target;
+        return true;
+    }
+
+    pos.0 += dx / left * dist;
+    pos.1 += dy / left * dist;
+
+    false
+}
@@ -0,0 +1,11 @@
+use yaml_rust::Yaml;
+
+// YAML tells whole numbers from the rest,
+// wherever a number is expected either will do
+pub fn as_f32(value: &Yaml) -> Option<f32> {
+    match value {
+        Yaml::Integer(i) => Some(*i as f32),
+        Yaml::Real(_) => value.as_f64().map(|f| f as f32),
+        _ => None,
+    }
+}